@@ -0,0 +1,93 @@
+//! async versions of the [`TreasuryClient`] fetch methods, enabled with the `async` feature
+//!
+//! the blocking fetch runs on the tokio blocking thread pool so the sync and async
+//! methods go through the same transport, retry, cache and parsing code
+use crate::{
+    client::TreasuryClient,
+    error::TreasuryCurveError,
+    treasury_curve::{TreasuryCurve, TreasuryCurveHistory},
+};
+use time::Date;
+
+impl TreasuryClient {
+    /// async version of [`TreasuryClient::fetch_latest`]
+    pub async fn fetch_latest_async(&self) -> Result<(Date, TreasuryCurve), TreasuryCurveError> {
+        let client = self.clone();
+        run_blocking(move || client.fetch_latest()).await
+    }
+
+    /// async version of [`TreasuryClient::fetch_date`]
+    pub async fn fetch_date_async(
+        &self,
+        request_date: Date,
+    ) -> Result<(Date, TreasuryCurve), TreasuryCurveError> {
+        let client = self.clone();
+        run_blocking(move || client.fetch_date(request_date)).await
+    }
+
+    /// async version of [`TreasuryClient::fetch_year`]
+    pub async fn fetch_year_async(
+        &self,
+        year: i32,
+    ) -> Result<TreasuryCurveHistory, TreasuryCurveError> {
+        let client = self.clone();
+        run_blocking(move || client.fetch_year(year)).await
+    }
+
+    /// async version of [`TreasuryClient::fetch_month`]
+    pub async fn fetch_month_async(
+        &self,
+        year: i32,
+        month: time::Month,
+    ) -> Result<TreasuryCurveHistory, TreasuryCurveError> {
+        let client = self.clone();
+        run_blocking(move || client.fetch_month(year, month)).await
+    }
+
+    /// async version of [`TreasuryClient::fetch_range`]
+    pub async fn fetch_range_async(
+        &self,
+        start: Date,
+        end: Date,
+    ) -> Result<TreasuryCurveHistory, TreasuryCurveError> {
+        let client = self.clone();
+        run_blocking(move || client.fetch_range(start, end)).await
+    }
+}
+
+async fn run_blocking<T, F>(f: F) -> Result<T, TreasuryCurveError>
+where
+    F: FnOnce() -> Result<T, TreasuryCurveError> + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(e) => Err(TreasuryCurveError::TaskCancelled(e.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{fixture_client, recorded_years};
+    use time::Date;
+
+    #[tokio::test]
+    async fn async_matches_sync() {
+        let (client, _) = fixture_client(recorded_years());
+        assert_eq!(
+            client.fetch_year_async(2023).await.unwrap(),
+            client.fetch_year(2023).unwrap()
+        );
+
+        let date = Date::from_calendar_date(2023, time::Month::January, 2).unwrap();
+        assert_eq!(
+            client.fetch_date_async(date).await.unwrap(),
+            client.fetch_date(date).unwrap()
+        );
+        assert_eq!(
+            client.fetch_year_async(2021).await.unwrap_err(),
+            client.fetch_year(2021).unwrap_err()
+        );
+    }
+}
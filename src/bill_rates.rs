@@ -0,0 +1,205 @@
+use crate::{
+    dataset::{DatasetHistory, TreasuryDataset},
+    request::BILL_RATES,
+};
+use time::Date;
+
+const BILL_MATURITIES: usize = 7;
+// bank discount and coupon equivalent column for each maturity
+const BILL_RATES_LENGTH: usize = 2 * BILL_MATURITIES;
+const BILL_RATES_HEADERS: [&str; BILL_RATES_LENGTH] = [
+    "4 WEEKS BANK DISCOUNT",
+    "4 WEEKS COUPON EQUIVALENT",
+    "6 WEEKS BANK DISCOUNT",
+    "6 WEEKS COUPON EQUIVALENT",
+    "8 WEEKS BANK DISCOUNT",
+    "8 WEEKS COUPON EQUIVALENT",
+    "13 WEEKS BANK DISCOUNT",
+    "13 WEEKS COUPON EQUIVALENT",
+    "17 WEEKS BANK DISCOUNT",
+    "17 WEEKS COUPON EQUIVALENT",
+    "26 WEEKS BANK DISCOUNT",
+    "26 WEEKS COUPON EQUIVALENT",
+    "52 WEEKS BANK DISCOUNT",
+    "52 WEEKS COUPON EQUIVALENT",
+];
+// field names used by the xml feed, same order as `BILL_RATES_HEADERS`
+const BILL_RATES_XML_FIELDS: [&str; BILL_RATES_LENGTH] = [
+    "ROUND_B1_CLOSE_4WK_2",
+    "ROUND_B1_YIELD_4WK_2",
+    "ROUND_B1_CLOSE_6WK_2",
+    "ROUND_B1_YIELD_6WK_2",
+    "ROUND_B1_CLOSE_8WK_2",
+    "ROUND_B1_YIELD_8WK_2",
+    "ROUND_B1_CLOSE_13WK_2",
+    "ROUND_B1_YIELD_13WK_2",
+    "ROUND_B1_CLOSE_17WK_2",
+    "ROUND_B1_YIELD_17WK_2",
+    "ROUND_B1_CLOSE_26WK_2",
+    "ROUND_B1_YIELD_26WK_2",
+    "ROUND_B1_CLOSE_52WK_2",
+    "ROUND_B1_YIELD_52WK_2",
+];
+
+/// Bill maturities published in the daily bill rates
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BillMaturity {
+    Wk4,
+    Wk6,
+    Wk8,
+    Wk13,
+    Wk17,
+    Wk26,
+    Wk52,
+}
+
+impl BillMaturity {
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// weeks to maturity
+    pub fn weeks(&self) -> u16 {
+        match self {
+            BillMaturity::Wk4 => 4,
+            BillMaturity::Wk6 => 6,
+            BillMaturity::Wk8 => 8,
+            BillMaturity::Wk13 => 13,
+            BillMaturity::Wk17 => 17,
+            BillMaturity::Wk26 => 26,
+            BillMaturity::Wk52 => 52,
+        }
+    }
+}
+
+/// Rates for one bill maturity, `None` when not published for the date
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BillRate {
+    pub bank_discount: Option<f64>,
+    pub coupon_equivalent: Option<f64>,
+}
+
+/// Captures the bill rates for a single date
+/// order of data matches `BillMaturity` with the bank discount before the coupon equivalent
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BillRates([Option<f64>; BILL_RATES_LENGTH]);
+
+impl BillRates {
+    pub fn get(&self, maturity: BillMaturity) -> BillRate {
+        let index = 2 * maturity.index();
+        BillRate {
+            bank_discount: self.0[index],
+            coupon_equivalent: self.0[index + 1],
+        }
+    }
+
+    pub fn bank_discount(&self, maturity: BillMaturity) -> Option<f64> {
+        self.get(maturity).bank_discount
+    }
+
+    pub fn coupon_equivalent(&self, maturity: BillMaturity) -> Option<f64> {
+        self.get(maturity).coupon_equivalent
+    }
+}
+
+impl TreasuryDataset for BillRates {
+    const DATA_TYPE: &'static str = BILL_RATES;
+    const HEADERS: &'static [&'static str] = &BILL_RATES_HEADERS;
+    const XML_FIELDS: &'static [&'static str] = &BILL_RATES_XML_FIELDS;
+
+    fn from_values(values: &[Option<f64>]) -> Self {
+        BillRates(values.try_into().expect("one value per header"))
+    }
+
+    fn values(&self) -> Vec<Option<f64>> {
+        self.0.to_vec()
+    }
+}
+
+/// Hold bill rates history
+/// history stored in reverse with latest at top
+pub type BillRatesHistory = DatasetHistory<BillRates>;
+
+impl BillRatesHistory {
+    /// rates for one maturity on every date, latest first
+    pub fn maturity(&self, maturity: BillMaturity) -> Vec<(Date, BillRate)> {
+        self.dates
+            .iter()
+            .zip(&self.records)
+            .map(|(date, rates)| (*date, rates.get(maturity)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::treasury_curve::{TreasuryCurveCsv, TreasuryCurveXml};
+
+    fn bill_csv_data() -> &'static str {
+        r###"Date,"4 WEEKS BANK DISCOUNT","4 WEEKS COUPON EQUIVALENT","8 WEEKS BANK DISCOUNT","8 WEEKS COUPON EQUIVALENT","13 WEEKS BANK DISCOUNT","13 WEEKS COUPON EQUIVALENT","17 WEEKS BANK DISCOUNT","17 WEEKS COUPON EQUIVALENT","26 WEEKS BANK DISCOUNT","26 WEEKS COUPON EQUIVALENT","52 WEEKS BANK DISCOUNT","52 WEEKS COUPON EQUIVALENT"
+07/07/2023,5.17,5.28,5.25,5.39,5.28,5.44,5.30,5.50,5.30,5.53,5.17,5.47
+07/06/2023,5.16,5.27,5.24,5.38,5.27,5.43,5.31,5.51,5.32,5.55,5.20,5.50
+07/05/2023,5.14,5.25,5.24,5.38,5.27,5.43,5.30,5.50,5.29,5.52,5.14,5.43"###
+    }
+
+    #[test]
+    fn parse_bill_rates_csv() {
+        let history =
+            BillRatesHistory::try_from(TreasuryCurveCsv(bill_csv_data().to_string())).unwrap();
+        assert_eq!(history.len(), 3);
+        let (date, rates) = history.latest();
+        assert_eq!(
+            date,
+            Date::from_calendar_date(2023, time::Month::July, 7).unwrap()
+        );
+        assert_eq!(
+            rates.get(BillMaturity::Wk4),
+            BillRate {
+                bank_discount: Some(5.17),
+                coupon_equivalent: Some(5.28)
+            }
+        );
+        assert_eq!(rates.coupon_equivalent(BillMaturity::Wk52), Some(5.47));
+        // 6 week bill was not part of the file
+        assert_eq!(rates.get(BillMaturity::Wk6), BillRate::default());
+    }
+
+    #[test]
+    fn bill_rates_by_date_and_maturity() {
+        let history =
+            BillRatesHistory::try_from(TreasuryCurveCsv(bill_csv_data().to_string())).unwrap();
+        let (date, rates) = history
+            .from_date(Date::from_calendar_date(2023, time::Month::July, 9).unwrap())
+            .unwrap();
+        assert_eq!(
+            date,
+            Date::from_calendar_date(2023, time::Month::July, 7).unwrap()
+        );
+        assert_eq!(rates.bank_discount(BillMaturity::Wk13), Some(5.28));
+
+        let wk26: Vec<Option<f64>> = history
+            .maturity(BillMaturity::Wk26)
+            .iter()
+            .map(|(_, rate)| rate.bank_discount)
+            .collect();
+        assert_eq!(wk26, vec![Some(5.30), Some(5.32), Some(5.29)]);
+    }
+
+    #[test]
+    fn parse_bill_rates_xml() {
+        let xml = crate::xml::feed(
+            r#"<m:properties>
+            <d:INDEX_DATE m:type="Edm.DateTime">2023-07-07T00:00:00</d:INDEX_DATE>
+            <d:NEW_DATE m:type="Edm.DateTime">2023-07-07T00:00:00</d:NEW_DATE>
+            <d:ROUND_B1_CLOSE_4WK_2 m:type="Edm.Double">5.17</d:ROUND_B1_CLOSE_4WK_2>
+            <d:ROUND_B1_YIELD_4WK_2 m:type="Edm.Double">5.28</d:ROUND_B1_YIELD_4WK_2>
+            <d:ROUND_B1_CLOSE_6WK_2 m:type="Edm.Double" m:null="true" />
+        </m:properties>"#,
+        );
+        let history = BillRatesHistory::try_from(TreasuryCurveXml(xml)).unwrap();
+        let (_, rates) = history.latest();
+        assert_eq!(rates.coupon_equivalent(BillMaturity::Wk4), Some(5.28));
+        assert_eq!(rates.bank_discount(BillMaturity::Wk6), None);
+    }
+}
@@ -0,0 +1,452 @@
+//! Cache of downloaded csv data keyed by [`Period`]
+//!
+//! past years and months never change (apart from rare revisions) so once downloaded after the period
+//! ended they are kept until invalidated, anything downloaded while its period was still current is only
+//! fresh for the cache `ttl` as new curves are published every working day.
+//! Entries are kept in memory unless a directory is given, in which case each period is stored
+//! as `{year}.csv` or `{year}-{month}.csv` and survives restarts. The xml feed is kept apart in
+//! [`Cache::xml`] as `{year}.xml` so switching source never reads one format as the other.
+use crate::{error::TreasuryCurveError, period::Period};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime},
+};
+use time::OffsetDateTime;
+
+/// Information about one cached period
+#[derive(Clone, Debug, PartialEq)]
+pub struct CacheEntry {
+    pub period: Period,
+    /// file holding the data, `None` for the in memory cache
+    pub path: Option<PathBuf>,
+    /// size of the csv data in bytes
+    pub size: u64,
+    /// when the data was downloaded
+    pub fetched: SystemTime,
+    /// entry can be used without fetching again
+    pub fresh: bool,
+}
+
+/// Cache of csv data keyed by [`Period`]
+#[derive(Debug)]
+pub struct Cache {
+    dir: Option<PathBuf>,
+    // file extension of the stored bodies, `csv` or `xml`
+    extension: &'static str,
+    ttl: Duration,
+    memory: Mutex<HashMap<Period, (SystemTime, String)>>,
+    namespaces: Mutex<HashMap<String, Arc<Cache>>>,
+}
+
+impl Cache {
+    /// cache held in memory for the life of the value
+    pub fn in_memory(ttl: Duration) -> Self {
+        Cache {
+            dir: None,
+            extension: "csv",
+            ttl,
+            memory: Mutex::new(HashMap::new()),
+            namespaces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// cache stored in `dir`, the directory is created on first write
+    pub fn on_disk(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Cache {
+            dir: Some(dir.into()),
+            ..Cache::in_memory(ttl)
+        }
+    }
+
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// separate cache with the same ttl for another dataset, kept in the `name` subdirectory
+    /// when on disk, [`list`](Cache::list) and [`clear`](Cache::clear) don't include it
+    pub fn namespace(&self, name: &str) -> Arc<Cache> {
+        self.child(
+            name,
+            self.dir.as_ref().map(|dir| dir.join(name)),
+            self.extension,
+        )
+    }
+
+    /// separate cache with the same ttl for the xml feed, stored next to the csv files with
+    /// the `xml` extension when on disk
+    pub fn xml(&self) -> Arc<Cache> {
+        // the key can't clash with a namespace as those are data type names
+        self.child("*.xml", self.dir.clone(), "xml")
+    }
+
+    fn child(&self, key: &str, dir: Option<PathBuf>, extension: &'static str) -> Arc<Cache> {
+        let mut namespaces = self.namespaces.lock().unwrap_or_else(|e| e.into_inner());
+        namespaces
+            .entry(key.to_string())
+            .or_insert_with(|| {
+                Arc::new(Cache {
+                    dir,
+                    extension,
+                    ..Cache::in_memory(self.ttl)
+                })
+            })
+            .clone()
+    }
+
+    /// grab csv data for the period if it exists and is still fresh
+    pub fn get(&self, period: impl Into<Period>) -> Result<Option<String>, TreasuryCurveError> {
+        let period = period.into();
+        match self.entry(period)? {
+            Some(entry) if entry.fresh => self.read(period),
+            _ => Ok(None),
+        }
+    }
+
+    /// grab csv data for the period if it exists, even if it is no longer fresh
+    pub fn get_stale(
+        &self,
+        period: impl Into<Period>,
+    ) -> Result<Option<String>, TreasuryCurveError> {
+        self.read(period.into())
+    }
+
+    pub fn insert(&self, period: impl Into<Period>, csv: &str) -> Result<(), TreasuryCurveError> {
+        let period = period.into();
+        match &self.dir {
+            Some(dir) => {
+                fs::create_dir_all(dir).map_err(|e| cache_error(dir, e))?;
+                // write to a temporary file first so readers never see a partial period, every
+                // write gets its own file as threads or clients may store the same period at once
+                static WRITES: AtomicU64 = AtomicU64::new(0);
+                let path = self.path(dir, period);
+                let tmp = path.with_extension(format!(
+                    "{}.{}.{}.tmp",
+                    self.extension,
+                    std::process::id(),
+                    WRITES.fetch_add(1, Ordering::Relaxed)
+                ));
+                fs::write(&tmp, csv).map_err(|e| cache_error(&tmp, e))?;
+                fs::rename(&tmp, &path).map_err(|e| cache_error(&path, e))
+            }
+            None => {
+                self.lock_memory()
+                    .insert(period, (SystemTime::now(), csv.to_string()));
+                Ok(())
+            }
+        }
+    }
+
+    /// details for a cached period
+    pub fn entry(
+        &self,
+        period: impl Into<Period>,
+    ) -> Result<Option<CacheEntry>, TreasuryCurveError> {
+        let period = period.into();
+        match &self.dir {
+            Some(dir) => {
+                let path = self.path(dir, period);
+                match fs::metadata(&path) {
+                    Ok(meta) => {
+                        let fetched = meta.modified().map_err(|e| cache_error(&path, e))?;
+                        Ok(Some(self.new_entry(
+                            period,
+                            Some(path),
+                            meta.len(),
+                            fetched,
+                        )))
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                    Err(e) => Err(cache_error(&path, e)),
+                }
+            }
+            None => Ok(self
+                .lock_memory()
+                .get(&period)
+                .map(|(fetched, csv)| self.new_entry(period, None, csv.len() as u64, *fetched))),
+        }
+    }
+
+    /// all cached periods ordered by start date, years before months starting on the same day
+    pub fn list(&self) -> Result<Vec<CacheEntry>, TreasuryCurveError> {
+        let mut periods: Vec<Period> = match &self.dir {
+            Some(dir) => match fs::read_dir(dir) {
+                Ok(read_dir) => read_dir
+                    .filter_map(|f| f.ok())
+                    .filter_map(|f| {
+                        period_from_file_name(&f.file_name().to_string_lossy(), self.extension)
+                    })
+                    .collect(),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+                Err(e) => return Err(cache_error(dir, e)),
+            },
+            None => self.lock_memory().keys().copied().collect(),
+        };
+        periods.sort_by_key(|p| (p.start(), matches!(p, Period::Month(..))));
+
+        let mut entries = Vec::with_capacity(periods.len());
+        for period in periods {
+            entries.extend(self.entry(period)?);
+        }
+        Ok(entries)
+    }
+
+    /// remove a period so it is fetched again, returns true if it was cached
+    pub fn invalidate(&self, period: impl Into<Period>) -> Result<bool, TreasuryCurveError> {
+        let period = period.into();
+        match &self.dir {
+            Some(dir) => {
+                let path = self.path(dir, period);
+                match fs::remove_file(&path) {
+                    Ok(()) => Ok(true),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+                    Err(e) => Err(cache_error(&path, e)),
+                }
+            }
+            None => Ok(self.lock_memory().remove(&period).is_some()),
+        }
+    }
+
+    /// remove every cached period
+    pub fn clear(&self) -> Result<(), TreasuryCurveError> {
+        for entry in self.list()? {
+            self.invalidate(entry.period)?;
+        }
+        Ok(())
+    }
+
+    fn read(&self, period: Period) -> Result<Option<String>, TreasuryCurveError> {
+        match &self.dir {
+            Some(dir) => {
+                let path = self.path(dir, period);
+                match fs::read_to_string(&path) {
+                    Ok(csv) => Ok(Some(csv)),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                    Err(e) => Err(cache_error(&path, e)),
+                }
+            }
+            None => Ok(self.lock_memory().get(&period).map(|(_, csv)| csv.clone())),
+        }
+    }
+
+    fn new_entry(
+        &self,
+        period: Period,
+        path: Option<PathBuf>,
+        size: u64,
+        fetched: SystemTime,
+    ) -> CacheEntry {
+        let age = fetched.elapsed().unwrap_or_default();
+        // only a download made after the period ended has every curve in it
+        let complete = OffsetDateTime::from(fetched).date() > period.end();
+        CacheEntry {
+            period,
+            path,
+            size,
+            fetched,
+            fresh: complete || age < self.ttl,
+        }
+    }
+
+    fn path(&self, dir: &Path, period: Period) -> PathBuf {
+        dir.join(format!("{period}.{}", self.extension))
+    }
+
+    fn lock_memory(&self) -> std::sync::MutexGuard<'_, HashMap<Period, (SystemTime, String)>> {
+        self.memory.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn period_from_file_name(name: &str, extension: &str) -> Option<Period> {
+    name.strip_suffix(extension)?
+        .strip_suffix('.')?
+        .parse()
+        .ok()
+}
+
+fn cache_error(path: &Path, e: std::io::Error) -> TreasuryCurveError {
+    TreasuryCurveError::Cache {
+        path: path.display().to_string(),
+        message: e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{current_year, test_util::temp_dir, utility::today};
+
+    #[test]
+    fn past_years_stay_and_current_year_expires() {
+        let cache = Cache::in_memory(Duration::ZERO);
+        cache.insert(2020, "past").unwrap();
+        cache.insert(current_year(), "current").unwrap();
+        assert_eq!(cache.get(2020).unwrap(), Some("past".to_string()));
+        assert_eq!(cache.get(current_year()).unwrap(), None);
+        assert_eq!(
+            cache.get_stale(current_year()).unwrap(),
+            Some("current".to_string())
+        );
+        assert_eq!(cache.get(2021).unwrap(), None);
+
+        let cache = Cache::in_memory(Duration::ZERO);
+        cache.insert(Period::month_of(today()), "month").unwrap();
+        cache
+            .insert(Period::month_of(today()).previous(), "previous")
+            .unwrap();
+        assert_eq!(cache.get(Period::month_of(today())).unwrap(), None);
+        assert_eq!(
+            cache.get(Period::month_of(today()).previous()).unwrap(),
+            Some("previous".to_string())
+        );
+
+        let cache = Cache::in_memory(Duration::from_secs(60));
+        cache.insert(current_year(), "current").unwrap();
+        assert_eq!(
+            cache.get(current_year()).unwrap(),
+            Some("current".to_string())
+        );
+    }
+
+    #[test]
+    fn period_cached_while_current_goes_stale_after_rollover() {
+        let cache = Cache::in_memory(Duration::from_secs(60 * 60));
+        let fetched_on =
+            |date: time::Date| SystemTime::from(date.with_hms(18, 0, 0).unwrap().assume_utc());
+        let december = |day| time::Date::from_calendar_date(2020, time::Month::December, day);
+        let january = time::Date::from_calendar_date(2021, time::Month::January, 4).unwrap();
+        // downloaded on 15 Dec 2020 while both were still receiving curves
+        cache.lock_memory().extend([
+            (
+                Period::Year(2020),
+                (fetched_on(december(15).unwrap()), "partial".to_string()),
+            ),
+            (
+                Period::Month(2020, time::Month::December),
+                (fetched_on(december(15).unwrap()), "partial".to_string()),
+            ),
+            (
+                Period::Year(2019),
+                (fetched_on(january), "complete".to_string()),
+            ),
+        ]);
+        assert!(!cache.entry(2020).unwrap().unwrap().fresh);
+        assert_eq!(cache.get(2020).unwrap(), None);
+        assert_eq!(
+            cache
+                .get(Period::Month(2020, time::Month::December))
+                .unwrap(),
+            None
+        );
+        assert_eq!(cache.get(2019).unwrap(), Some("complete".to_string()));
+
+        // once downloaded again after the year ended it is kept
+        cache.lock_memory().insert(
+            Period::Year(2020),
+            (fetched_on(january), "complete".to_string()),
+        );
+        assert!(cache.entry(2020).unwrap().unwrap().fresh);
+    }
+
+    #[test]
+    fn disk_cache_lists_inspects_and_invalidates() {
+        let dir = temp_dir("disk");
+        let cache = Cache::on_disk(&dir, Duration::ZERO);
+        assert_eq!(cache.list().unwrap(), vec![]);
+
+        cache.insert(2021, "2021 data").unwrap();
+        cache
+            .insert(Period::Month(2021, time::Month::March), "march")
+            .unwrap();
+        cache.insert(2020, "2020").unwrap();
+        cache.insert(current_year(), "current").unwrap();
+        fs::write(dir.join("notes.txt"), "not a year").unwrap();
+
+        // a new cache on the same directory sees the stored years
+        let cache = Cache::on_disk(&dir, Duration::ZERO);
+        assert_eq!(cache.get(2021).unwrap(), Some("2021 data".to_string()));
+        let entries = cache.list().unwrap();
+        let periods: Vec<Period> = entries.iter().map(|e| e.period).collect();
+        assert_eq!(
+            periods,
+            vec![
+                Period::Year(2020),
+                Period::Year(2021),
+                Period::Month(2021, time::Month::March),
+                Period::Year(current_year())
+            ]
+        );
+        assert_eq!(entries[1].size, 9);
+        assert_eq!(entries[1].path, Some(dir.join("2021.csv")));
+        assert_eq!(entries[2].path, Some(dir.join("2021-03.csv")));
+        assert!(entries[1].fresh);
+        assert!(!entries[3].fresh);
+
+        assert!(cache.invalidate(2021).unwrap());
+        assert!(!cache.invalidate(2021).unwrap());
+        assert_eq!(cache.entry(2021).unwrap(), None);
+
+        let real = cache.namespace("real");
+        real.insert(2021, "real 2021").unwrap();
+        assert_eq!(
+            real.entry(2021).unwrap().unwrap().path,
+            Some(dir.join("real").join("2021.csv"))
+        );
+        assert_eq!(
+            cache.namespace("real").get(2021).unwrap(),
+            Some("real 2021".to_string())
+        );
+        assert_eq!(cache.get(2021).unwrap(), None);
+
+        let xml = cache.xml();
+        xml.insert(2022, "<feed></feed>").unwrap();
+        assert_eq!(
+            xml.entry(2022).unwrap().unwrap().path,
+            Some(dir.join("2022.xml"))
+        );
+        assert_eq!(cache.get(2022).unwrap(), None);
+        assert_eq!(
+            xml.list()
+                .unwrap()
+                .iter()
+                .map(|e| e.period)
+                .collect::<Vec<_>>(),
+            vec![Period::Year(2022)]
+        );
+
+        cache.clear().unwrap();
+        assert_eq!(cache.list().unwrap(), vec![]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn concurrent_writes_of_a_period_never_mix() {
+        let dir = temp_dir("concurrent");
+        let cache = Arc::new(Cache::on_disk(&dir, Duration::ZERO));
+        let bodies: Vec<String> = (0..8).map(|i| i.to_string().repeat(64 * 1024)).collect();
+        std::thread::scope(|scope| {
+            for body in &bodies {
+                let cache = cache.clone();
+                scope.spawn(move || {
+                    for _ in 0..10 {
+                        cache.insert(2020, body).unwrap();
+                    }
+                });
+            }
+        });
+        let stored = cache.get(2020).unwrap().unwrap();
+        assert!(bodies.contains(&stored));
+        // no temporary files are left behind
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,1150 @@
+use crate::{
+    bill_rates::{BillRates, BillRatesHistory},
+    cache::{Cache, CacheEntry},
+    current_year,
+    dataset::{DatasetHistory, ParseOptions, TreasuryDataset},
+    error::TreasuryCurveError,
+    long_term_rates::{
+        LongTermRate, LongTermRateHistory, RealLongTermRate, RealLongTermRateHistory,
+    },
+    period::Period,
+    real_yield_curve::{RealYieldCurve, RealYieldCurveHistory},
+    request::{check_content, check_status, treasury_url, Source, YIELD_CURVE},
+    retry::RetryPolicy,
+    transport::{default_transport, Transport, TransportConfig},
+    treasury_curve::{TreasuryCurve, TreasuryCurveHistory},
+    utility::today,
+    MIN_YEAR_AVAIL,
+};
+use std::{
+    fmt,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+use time::{Date, Month};
+
+/// csv endpoint for the daily treasury rates
+pub const DEFAULT_BASE_URL: &str =
+    "https://home.treasury.gov/resource-center/data-chart-center/interest-rates/daily-treasury-rates.csv";
+
+/// xml (OData) endpoint for the daily treasury rates, used with [`Source::Xml`]
+pub const DEFAULT_XML_BASE_URL: &str =
+    "https://home.treasury.gov/resource-center/data-chart-center/interest-rates/pages/xml";
+
+/// number of years downloaded in parallel by multi-year fetches
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// how long the current year or month is kept in the cache before fetching again
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Reusable client to fetch Treasury curves
+///
+/// holds one [`Transport`] that reuses its connection across calls and caches downloaded data,
+/// create with [`TreasuryClient::builder`] or [`TreasuryClient::default`]
+///
+/// cloning is cheap and the clones share the transport and cache
+#[derive(Clone)]
+pub struct TreasuryClient {
+    base_url: String,
+    source: Source,
+    parse: ParseOptions,
+    retry: RetryPolicy,
+    cache: Option<Arc<Cache>>,
+    offline: bool,
+    concurrency: usize,
+    transport: Arc<dyn Transport>,
+}
+
+impl fmt::Debug for TreasuryClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TreasuryClient")
+            .field("base_url", &self.base_url)
+            .field("source", &self.source)
+            .field("parse", &self.parse)
+            .field("retry", &self.retry)
+            .field("cache", &self.cache)
+            .field("offline", &self.offline)
+            .field("concurrency", &self.concurrency)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for TreasuryClient {
+    fn default() -> Self {
+        TreasuryClientBuilder::default().build()
+    }
+}
+
+impl TreasuryClient {
+    pub fn builder() -> TreasuryClientBuilder {
+        TreasuryClientBuilder::default()
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn source(&self) -> Source {
+        self.source
+    }
+
+    pub fn parse_options(&self) -> ParseOptions {
+        self.parse
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// cache used by the client, `None` when caching is turned off
+    /// the xml source keeps its bodies in [`Cache::xml`]
+    pub fn cache(&self) -> Option<&Cache> {
+        self.cache.as_deref()
+    }
+
+    /// download any of `years` that are missing or stale in the cache,
+    /// ie. before running in [`offline`](TreasuryClientBuilder::offline) mode
+    pub fn prewarm_cache(
+        &self,
+        years: impl IntoIterator<Item = i32>,
+    ) -> Result<Vec<CacheEntry>, TreasuryCurveError> {
+        let cache = self
+            .cache_for(YIELD_CURVE)
+            .ok_or(TreasuryCurveError::CacheDisabled)?;
+        let years: Vec<i32> = years.into_iter().collect();
+        self.for_each_year(&years, |year| {
+            self.fetch_body(YIELD_CURVE, Period::Year(year))
+        })?;
+        let mut entries = vec![];
+        for year in years {
+            entries.extend(cache.entry(year)?);
+        }
+        Ok(entries)
+    }
+
+    /// fetch the latest date of the Tresury Curve
+    /// only downloads the current month, or the month before if nothing is published yet
+    pub fn fetch_latest(&self) -> Result<(Date, TreasuryCurve), TreasuryCurveError> {
+        self.fetch_dataset_latest()
+    }
+
+    /// fetch a specific date of the Tresury curve
+    /// Defaults to the last known data point on weekend and holidays
+    pub fn fetch_date(
+        &self,
+        request_date: Date,
+    ) -> Result<(Date, TreasuryCurve), TreasuryCurveError> {
+        self.fetch_dataset_date(request_date)
+    }
+
+    /// fetch an entire year of Treasury curves
+    pub fn fetch_year(&self, year: i32) -> Result<TreasuryCurveHistory, TreasuryCurveError> {
+        self.fetch_dataset_year(year)
+    }
+
+    /// fetch one month of Treasury curves, the history is empty if nothing is published yet
+    pub fn fetch_month(
+        &self,
+        year: i32,
+        month: Month,
+    ) -> Result<TreasuryCurveHistory, TreasuryCurveError> {
+        self.fetch_dataset_month(year, month)
+    }
+
+    /// fetch the latest real (TIPS) yield curve
+    pub fn fetch_real_latest(&self) -> Result<(Date, RealYieldCurve), TreasuryCurveError> {
+        self.fetch_dataset_latest()
+    }
+
+    /// fetch the real (TIPS) yield curve for a specific date
+    pub fn fetch_real_date(
+        &self,
+        request_date: Date,
+    ) -> Result<(Date, RealYieldCurve), TreasuryCurveError> {
+        self.fetch_dataset_date(request_date)
+    }
+
+    /// fetch an entire year of real (TIPS) yield curve
+    pub fn fetch_real_year(&self, year: i32) -> Result<RealYieldCurveHistory, TreasuryCurveError> {
+        self.fetch_dataset_year(year)
+    }
+
+    /// fetch one month of real (TIPS) yield curve
+    pub fn fetch_real_month(
+        &self,
+        year: i32,
+        month: Month,
+    ) -> Result<RealYieldCurveHistory, TreasuryCurveError> {
+        self.fetch_dataset_month(year, month)
+    }
+
+    /// fetch the latest bill rates
+    pub fn fetch_bill_rates_latest(&self) -> Result<(Date, BillRates), TreasuryCurveError> {
+        self.fetch_dataset_latest()
+    }
+
+    /// fetch the bill rates for a specific date
+    pub fn fetch_bill_rates_date(
+        &self,
+        request_date: Date,
+    ) -> Result<(Date, BillRates), TreasuryCurveError> {
+        self.fetch_dataset_date(request_date)
+    }
+
+    /// fetch an entire year of bill rates
+    pub fn fetch_bill_rates_year(&self, year: i32) -> Result<BillRatesHistory, TreasuryCurveError> {
+        self.fetch_dataset_year(year)
+    }
+
+    /// fetch one month of bill rates
+    pub fn fetch_bill_rates_month(
+        &self,
+        year: i32,
+        month: Month,
+    ) -> Result<BillRatesHistory, TreasuryCurveError> {
+        self.fetch_dataset_month(year, month)
+    }
+
+    /// fetch the latest long-term rates
+    pub fn fetch_long_term_latest(&self) -> Result<(Date, LongTermRate), TreasuryCurveError> {
+        self.fetch_dataset_latest()
+    }
+
+    /// fetch the long-term rates for a specific date
+    pub fn fetch_long_term_date(
+        &self,
+        request_date: Date,
+    ) -> Result<(Date, LongTermRate), TreasuryCurveError> {
+        self.fetch_dataset_date(request_date)
+    }
+
+    /// fetch an entire year of long-term rates
+    pub fn fetch_long_term_year(
+        &self,
+        year: i32,
+    ) -> Result<LongTermRateHistory, TreasuryCurveError> {
+        self.fetch_dataset_year(year)
+    }
+
+    /// fetch one month of long-term rates
+    pub fn fetch_long_term_month(
+        &self,
+        year: i32,
+        month: Month,
+    ) -> Result<LongTermRateHistory, TreasuryCurveError> {
+        self.fetch_dataset_month(year, month)
+    }
+
+    /// fetch the latest real long-term rates
+    pub fn fetch_real_long_term_latest(
+        &self,
+    ) -> Result<(Date, RealLongTermRate), TreasuryCurveError> {
+        self.fetch_dataset_latest()
+    }
+
+    /// fetch the real long-term rates for a specific date
+    pub fn fetch_real_long_term_date(
+        &self,
+        request_date: Date,
+    ) -> Result<(Date, RealLongTermRate), TreasuryCurveError> {
+        self.fetch_dataset_date(request_date)
+    }
+
+    /// fetch an entire year of real long-term rates
+    pub fn fetch_real_long_term_year(
+        &self,
+        year: i32,
+    ) -> Result<RealLongTermRateHistory, TreasuryCurveError> {
+        self.fetch_dataset_year(year)
+    }
+
+    /// fetch one month of real long-term rates
+    pub fn fetch_real_long_term_month(
+        &self,
+        year: i32,
+        month: Month,
+    ) -> Result<RealLongTermRateHistory, TreasuryCurveError> {
+        self.fetch_dataset_month(year, month)
+    }
+
+    /// fetch every curve from `start` to `end` inclusive, merging the years needed into one history
+    /// a `start` before the first published year (1990) is fine, the history begins at the data
+    pub fn fetch_range(
+        &self,
+        start: Date,
+        end: Date,
+    ) -> Result<TreasuryCurveHistory, TreasuryCurveError> {
+        self.fetch_dataset_range(start, end)
+    }
+
+    /// fetch several years at once, downloading up to the client
+    /// [`concurrency`](TreasuryClientBuilder::concurrency) limit in parallel
+    ///
+    /// results are in the same order as `years`, if any year fails the errors for
+    /// every failed year are returned together in [`TreasuryCurveError::YearErrors`]
+    pub fn fetch_years(
+        &self,
+        years: &[i32],
+    ) -> Result<Vec<(i32, TreasuryCurveHistory)>, TreasuryCurveError> {
+        self.fetch_dataset_years(years)
+    }
+
+    /// fetch the latest record of any dataset, ie. `client.fetch_dataset_latest::<BillRates>()`
+    /// only downloads the current month, or the month before if nothing is published yet
+    pub fn fetch_dataset_latest<D: TreasuryDataset>(
+        &self,
+    ) -> Result<(Date, D), TreasuryCurveError> {
+        let month = Period::month_of(today());
+        let mut history = self.fetch_dataset_period::<D>(month)?;
+        if history.is_empty() {
+            history = self.fetch_dataset_period(month.previous())?;
+        }
+        history
+            .try_latest()
+            .ok_or_else(|| TreasuryCurveError::OutsideDateRange(today().to_string()))
+    }
+
+    /// fetch the record of any dataset for a specific date
+    /// Defaults to the last known data point on weekend and holidays
+    pub fn fetch_dataset_date<D: TreasuryDataset>(
+        &self,
+        request_date: Date,
+    ) -> Result<(Date, D), TreasuryCurveError> {
+        if request_date.year() > current_year() {
+            return Err(TreasuryCurveError::InvalidYear(request_date.year()));
+        }
+        // dates up to 5 days past the last record are allowed so a date in the future
+        // is looked up in the current month
+        let month = Period::month_of(request_date.min(today()));
+        let history = self.fetch_dataset_period::<D>(month)?;
+        // the month may have nothing on or before the date because of weekends and holidays
+        // or because nothing is published yet, the month prior is checked with the same 5 day limit
+        match history.dates().last() {
+            Some(first) if request_date >= *first => history.from_date(request_date),
+            _ => self
+                .fetch_dataset_period::<D>(month.previous())?
+                .from_date(request_date),
+        }
+    }
+
+    /// fetch an entire year of any dataset
+    pub fn fetch_dataset_year<D: TreasuryDataset>(
+        &self,
+        year: i32,
+    ) -> Result<DatasetHistory<D>, TreasuryCurveError> {
+        self.fetch_dataset_period(Period::Year(year))
+    }
+
+    /// fetch one month of any dataset, the history is empty if nothing is published yet
+    pub fn fetch_dataset_month<D: TreasuryDataset>(
+        &self,
+        year: i32,
+        month: Month,
+    ) -> Result<DatasetHistory<D>, TreasuryCurveError> {
+        self.fetch_dataset_period(Period::Month(year, month))
+    }
+
+    /// fetch every record of any dataset from `start` to `end` inclusive
+    pub fn fetch_dataset_range<D: TreasuryDataset>(
+        &self,
+        start: Date,
+        end: Date,
+    ) -> Result<DatasetHistory<D>, TreasuryCurveError> {
+        if start > end {
+            return Err(TreasuryCurveError::InvalidDateRange(format!(
+                "{start} is after {end}"
+            )));
+        }
+        // no data is published before 1990 or past the current year so don't ask for it
+        let years: Vec<i32> =
+            (start.year().max(MIN_YEAR_AVAIL)..=end.year().min(current_year())).collect();
+        let mut histories = self.fetch_dataset_years::<D>(&years)?.into_iter();
+        let (_, mut history) = histories
+            .next()
+            .ok_or(TreasuryCurveError::InvalidYear(start.year()))?;
+        for (_, other) in histories {
+            history.merge(other);
+        }
+
+        history.retain_range(start, end);
+        if history.is_empty() {
+            return Err(TreasuryCurveError::OutsideDateRange(format!(
+                "{start} to {end}"
+            )));
+        }
+        Ok(history)
+    }
+
+    /// fetch several years of any dataset at once, see [`TreasuryClient::fetch_years`]
+    pub fn fetch_dataset_years<D: TreasuryDataset>(
+        &self,
+        years: &[i32],
+    ) -> Result<Vec<(i32, DatasetHistory<D>)>, TreasuryCurveError> {
+        self.for_each_year(years, |year| self.fetch_dataset_year(year))
+    }
+
+    /// run `f` for every year on a bounded pool of scoped threads
+    fn for_each_year<T, F>(&self, years: &[i32], f: F) -> Result<Vec<(i32, T)>, TreasuryCurveError>
+    where
+        T: Send,
+        F: Fn(i32) -> Result<T, TreasuryCurveError> + Sync,
+    {
+        let next = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<Result<T, TreasuryCurveError>>>> =
+            Mutex::new(years.iter().map(|_| None).collect());
+        let workers = self.concurrency.clamp(1, years.len().max(1));
+
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(year) = years.get(index) else {
+                        break;
+                    };
+                    let result = f(*year);
+                    results.lock().unwrap_or_else(|e| e.into_inner())[index] = Some(result);
+                });
+            }
+        });
+
+        let mut values = vec![];
+        let mut errors = vec![];
+        let results = results.into_inner().unwrap_or_else(|e| e.into_inner());
+        for (year, result) in years.iter().zip(results) {
+            match result.expect("every year is processed before the scope ends") {
+                Ok(value) => values.push((*year, value)),
+                Err(e) => errors.push((*year, e)),
+            }
+        }
+        if errors.is_empty() {
+            Ok(values)
+        } else {
+            Err(TreasuryCurveError::YearErrors(errors))
+        }
+    }
+
+    fn fetch_dataset_period<D: TreasuryDataset>(
+        &self,
+        period: Period,
+    ) -> Result<DatasetHistory<D>, TreasuryCurveError> {
+        if self.source == Source::Xml && D::XML_FIELDS.is_empty() {
+            return Err(TreasuryCurveError::UnsupportedSource(format!(
+                "{} as xml",
+                D::DATA_TYPE
+            )));
+        }
+        let body = self.fetch_body(D::DATA_TYPE, period)?;
+        let mut history = match self.source {
+            Source::Csv => DatasetHistory::from_csv(&body, self.parse)?,
+            Source::Xml => DatasetHistory::from_xml(&body)?,
+        };
+        if let Period::Month(..) = period {
+            // the data may be for the whole year when it was already cached
+            history.retain_range(period.start(), period.end());
+        }
+        Ok(history)
+    }
+
+    /// Fetch csv or xml data of the `data` type for a period, from the cache if available
+    /// a month can be served from its year so the data may hold more than the period
+    pub(crate) fn fetch_body(
+        &self,
+        data: &str,
+        period: Period,
+    ) -> Result<String, TreasuryCurveError> {
+        let url = treasury_url(&self.base_url, self.source, data, period)?;
+        let cache = self.cache_for(data);
+        let mut cached = vec![period];
+        if let Period::Month(year, _) = period {
+            cached.push(Period::Year(year));
+        }
+        if let Some(cache) = &cache {
+            for p in &cached {
+                if let Some(csv) = cache.get(*p)? {
+                    return Ok(csv);
+                }
+            }
+            if self.offline {
+                for p in &cached {
+                    if let Some(csv) = cache.get_stale(*p)? {
+                        return Ok(csv);
+                    }
+                }
+            }
+        }
+        if self.offline {
+            return Err(TreasuryCurveError::NotCached(period.to_string()));
+        }
+
+        // maintenance and throttling pages can come back with status 200 so they are retried too
+        let buffer = self.retry.retry(|| {
+            let response = self.transport.get(&url)?;
+            check_status(&url, &response)?;
+            check_content(&url, self.source, response)
+        })?;
+        let csv = String::from_utf8(buffer)?;
+
+        if let Some(cache) = &cache {
+            cache.insert(period, &csv)?;
+        }
+        Ok(csv)
+    }
+
+    // the yield curve uses the client cache, other data types get their own namespace in it.
+    // xml bodies are kept apart from csv ones so switching source never mixes them up
+    fn cache_for(&self, data: &str) -> Option<Arc<Cache>> {
+        let cache = match self.source {
+            Source::Csv => self.cache.clone()?,
+            Source::Xml => self.cache.as_ref()?.xml(),
+        };
+        if data == YIELD_CURVE {
+            Some(cache)
+        } else {
+            Some(cache.namespace(data))
+        }
+    }
+}
+
+/// Builder for [`TreasuryClient`]
+pub struct TreasuryClientBuilder {
+    base_url: Option<String>,
+    source: Source,
+    parse: ParseOptions,
+    user_agent: Option<String>,
+    proxy: Option<String>,
+    retry: RetryPolicy,
+    cache: bool,
+    cache_ttl: Duration,
+    cache_dir: Option<PathBuf>,
+    offline: bool,
+    concurrency: usize,
+    transport: Option<Arc<dyn Transport>>,
+}
+
+impl Default for TreasuryClientBuilder {
+    fn default() -> Self {
+        TreasuryClientBuilder {
+            base_url: None,
+            source: Source::default(),
+            parse: ParseOptions::default(),
+            user_agent: None,
+            proxy: None,
+            retry: RetryPolicy::default(),
+            cache: true,
+            cache_ttl: DEFAULT_CACHE_TTL,
+            cache_dir: None,
+            offline: false,
+            concurrency: DEFAULT_CONCURRENCY,
+            transport: None,
+        }
+    }
+}
+
+impl TreasuryClientBuilder {
+    /// url the year and query parameters are appended to, defaults to [`DEFAULT_BASE_URL`]
+    /// or [`DEFAULT_XML_BASE_URL`] depending on the [`source`](TreasuryClientBuilder::source)
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    /// feed to download, [`Source::Csv`] by default
+    pub fn source(mut self, source: Source) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// keep csv columns that aren't known labels instead of failing, see [`ParseOptions::lenient`]
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.parse.lenient = lenient;
+        self
+    }
+
+    /// leave out csv rows that can't be read, see [`ParseOptions::skip_invalid_rows`]
+    pub fn skip_invalid_rows(mut self, skip: bool) -> Self {
+        self.parse.skip_invalid_rows = skip;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// proxy url, ie. `http://proxy.local:8080`
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// replaces the whole retry policy including its timeouts
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.retry.connect_timeout = timeout;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.retry.timeout = timeout;
+        self
+    }
+
+    /// keep downloaded years in memory, on by default
+    pub fn cache(mut self, enabled: bool) -> Self {
+        self.cache = enabled;
+        self
+    }
+
+    /// how long the current year stays cached, past years are kept until invalidated
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// store the cache in `dir` so it is shared between runs, turns the cache on
+    pub fn cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache = true;
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// never fetch from the network, years are served from the cache even when stale
+    /// and [`TreasuryCurveError::NotCached`] is returned for missing years
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// number of years downloaded in parallel by [`TreasuryClient::fetch_years`],
+    /// [`TreasuryClient::fetch_range`] and [`TreasuryClient::prewarm_cache`], at least 1
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// use `transport` to download data instead of the default for the enabled features,
+    /// the user agent, proxy and timeouts set on the builder are not applied to it
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    pub fn build(self) -> TreasuryClient {
+        let transport = self.transport.unwrap_or_else(|| {
+            default_transport(TransportConfig {
+                user_agent: self.user_agent,
+                proxy: self.proxy,
+                connect_timeout: self.retry.connect_timeout,
+                timeout: self.retry.timeout,
+            })
+        });
+        let base_url = self
+            .base_url
+            .unwrap_or_else(|| self.source.default_base_url().to_string());
+        TreasuryClient {
+            base_url,
+            source: self.source,
+            parse: self.parse,
+            retry: self.retry,
+            cache: self.cache.then(|| {
+                Arc::new(match self.cache_dir {
+                    Some(dir) => Cache::on_disk(dir, self.cache_ttl),
+                    None => Cache::in_memory(self.cache_ttl),
+                })
+            }),
+            offline: self.offline,
+            concurrency: self.concurrency,
+            transport,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        request::{period_url, BILL_RATES, REAL_LONG_TERM},
+        test_util::{fixture_client, temp_dir},
+        transport::{FixtureTransport, Response},
+        treasury_curve::Label,
+        MAX_FORWARD_DAYS,
+    };
+    use std::sync::Arc;
+    use time::ext::NumericalDuration;
+
+    fn csv_2022() -> String {
+        r###"Date,"1 Mo","2 Mo","3 Mo","4 Mo","6 Mo","1 Yr","2 Yr","3 Yr","5 Yr","7 Yr","10 Yr","20 Yr","30 Yr"
+12/30/2022,4.12,4.41,4.42,4.69,4.76,4.73,4.41,4.22,3.99,3.96,3.88,4.14,3.97
+12/29/2022,4.04,4.39,4.45,4.66,4.73,4.71,4.34,4.16,3.94,3.91,3.83,4.09,3.92"###
+            .to_string()
+    }
+
+    fn csv_2023() -> String {
+        r###"Date,"1 Mo","2 Mo","3 Mo","4 Mo","6 Mo","1 Yr","2 Yr","3 Yr","5 Yr","7 Yr","10 Yr","20 Yr","30 Yr"
+01/03/2023,4.17,4.42,4.53,4.70,4.77,4.72,4.40,4.18,3.94,3.89,3.79,4.06,3.88"###
+            .to_string()
+    }
+
+    #[test]
+    fn builder_sets_options() {
+        let builder = TreasuryClient::builder()
+            .base_url("http://localhost:8080/rates.csv/")
+            .user_agent("overnight-job")
+            .proxy("http://proxy.local:3128")
+            .connect_timeout(Duration::from_secs(1))
+            .timeout(Duration::from_secs(2))
+            .cache(false);
+        assert_eq!(builder.user_agent.as_deref(), Some("overnight-job"));
+        assert_eq!(builder.proxy.as_deref(), Some("http://proxy.local:3128"));
+
+        let client = builder.build();
+        assert_eq!(client.base_url(), "http://localhost:8080/rates.csv");
+        assert_eq!(
+            client.retry_policy().connect_timeout,
+            Duration::from_secs(1)
+        );
+        assert_eq!(client.retry_policy().timeout, Duration::from_secs(2));
+        assert!(client.cache.is_none());
+
+        let client = TreasuryClient::default();
+        assert_eq!(client.base_url(), DEFAULT_BASE_URL);
+        assert_eq!(client.retry_policy(), &RetryPolicy::default());
+        assert!(client.cache.is_some());
+    }
+
+    #[test]
+    fn cached_year_is_only_fetched_once() {
+        let (client, fixture) = fixture_client(FixtureTransport::new().with_year(2022, csv_2022()));
+
+        let history = client.fetch_year(2022).unwrap();
+        assert_eq!(
+            history.latest().0,
+            Date::from_calendar_date(2022, time::Month::December, 30).unwrap()
+        );
+        client.fetch_year(2022).unwrap();
+        assert_eq!(fixture.requests().len(), 1);
+
+        assert!(matches!(
+            client.fetch_year(2021),
+            Err(TreasuryCurveError::FetchFailed { attempts: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn prewarmed_disk_cache_runs_offline() {
+        let dir = temp_dir("prewarm");
+        let client = TreasuryClient::builder()
+            .retry_policy(RetryPolicy::no_retry())
+            .cache_dir(&dir)
+            .transport(
+                FixtureTransport::new()
+                    .with_year(2022, csv_2022())
+                    .with_year(2023, csv_2023()),
+            )
+            .build();
+        let entries = client.prewarm_cache([2022, 2023]).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].path, Some(dir.join("2022.csv")));
+
+        // a new offline client on the same directory never touches the network
+        let fixture = Arc::new(FixtureTransport::new());
+        let client = TreasuryClient::builder()
+            .cache_dir(&dir)
+            .offline(true)
+            .transport(fixture.clone())
+            .build();
+        let (_, curve) = client.fetch_year(2022).unwrap().latest();
+        assert_eq!(curve.get_label(Label::Mo2), Some(4.41));
+        assert_eq!(
+            client.fetch_year(2021).unwrap_err(),
+            TreasuryCurveError::NotCached("2021".to_string())
+        );
+        assert!(fixture.requests().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn fetch_range_merges_years() {
+        let (client, _) = fixture_client(
+            FixtureTransport::new()
+                .with_year(2022, csv_2022())
+                .with_year(2023, csv_2023()),
+        );
+        let history = client
+            .fetch_range(
+                Date::from_calendar_date(2022, time::Month::December, 28).unwrap(),
+                Date::from_calendar_date(2023, time::Month::January, 3).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(
+            history.latest().0,
+            Date::from_calendar_date(2023, time::Month::January, 3).unwrap()
+        );
+
+        let christmas = Date::from_calendar_date(2022, time::Month::December, 25).unwrap();
+        assert_eq!(
+            client.fetch_range(christmas, christmas).unwrap_err(),
+            TreasuryCurveError::OutsideDateRange("2022-12-25 to 2022-12-25".to_string())
+        );
+        assert!(matches!(
+            client.fetch_range(christmas, christmas.previous_day().unwrap()),
+            Err(TreasuryCurveError::InvalidDateRange(_))
+        ));
+    }
+
+    #[test]
+    fn fetch_range_starts_at_the_first_published_year() {
+        let csv_1990 = "Date,\"3 Mo\",\"1 Yr\",\"30 Yr\"\n01/02/1990,7.83,7.89,8.00";
+        let (client, fixture) = fixture_client(FixtureTransport::new().with_year(1990, csv_1990));
+        let start = Date::from_calendar_date(1985, time::Month::January, 1).unwrap();
+        let history = client
+            .fetch_range(
+                start,
+                Date::from_calendar_date(1990, time::Month::December, 31).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            history.dates(),
+            [Date::from_calendar_date(1990, time::Month::January, 2).unwrap()]
+        );
+        assert_eq!(fixture.requests().len(), 1);
+
+        // nothing is published that early
+        assert!(matches!(
+            client.fetch_range(
+                start,
+                Date::from_calendar_date(1989, time::Month::December, 31).unwrap()
+            ),
+            Err(TreasuryCurveError::InvalidYear(1985))
+        ));
+    }
+
+    // counts how many requests are in flight at the same time
+    #[derive(Default)]
+    struct SlowTransport {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    impl Transport for SlowTransport {
+        fn get(&self, _url: &str) -> Result<Response, TreasuryCurveError> {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(Response {
+                status: 404,
+                ..Response::ok("")
+            })
+        }
+    }
+
+    #[test]
+    fn fetch_years_is_bounded_and_collects_errors() {
+        let slow = Arc::new(SlowTransport::default());
+        let client = TreasuryClient::builder()
+            .retry_policy(RetryPolicy::no_retry())
+            .concurrency(3)
+            .transport(slow.clone())
+            .build();
+        let years: Vec<i32> = (2010..2020).collect();
+        match client.fetch_years(&years) {
+            Err(TreasuryCurveError::YearErrors(errors)) => {
+                let failed: Vec<i32> = errors.iter().map(|(year, _)| *year).collect();
+                assert_eq!(failed, years);
+            }
+            other => panic!("expected YearErrors, got {other:?}"),
+        }
+        let max = slow.max_in_flight.load(Ordering::SeqCst);
+        assert!(max > 1 && max <= 3, "max in flight was {max}");
+
+        let (client, _) = fixture_client(
+            FixtureTransport::new()
+                .with_year(2021, csv_2022())
+                .with_year(2023, csv_2023()),
+        );
+        match client.fetch_years(&[2021, 2022, 2023, 1980]) {
+            Err(TreasuryCurveError::YearErrors(errors)) => {
+                assert_eq!(errors.len(), 2);
+                assert_eq!(errors[0].0, 2022);
+                assert_eq!(errors[1], (1980, TreasuryCurveError::InvalidYear(1980)));
+            }
+            other => panic!("expected YearErrors, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn fetch_date_falls_back_to_prior_year() {
+        let (client, _) = fixture_client(
+            FixtureTransport::new()
+                .with_year(2022, csv_2022())
+                .with_year(2023, csv_2023()),
+        );
+
+        let (date, curve) = client
+            .fetch_date(Date::from_calendar_date(2023, time::Month::January, 2).unwrap())
+            .unwrap();
+        assert_eq!(
+            date,
+            Date::from_calendar_date(2022, time::Month::December, 30).unwrap()
+        );
+        assert_eq!(curve.get_label(Label::Mo2), Some(4.41));
+    }
+
+    #[test]
+    fn fetch_date_falls_back_when_the_month_is_not_published() {
+        // the current month has nothing yet and the last record is the end of the month prior
+        let previous = Period::month_of(today()).previous();
+        let last = previous.end();
+        let row = format!(
+            "{},4.12,4.41,4.42,4.69,4.76,4.73,4.41,4.22,3.99,3.96,3.88,4.14,3.97",
+            last.format(&crate::utility::date_format_header()).unwrap()
+        );
+        let header = csv_2022().lines().next().unwrap().to_string();
+        let current = Period::month_of(today());
+        let (client, _) = fixture_client(
+            FixtureTransport::new()
+                .with_month(
+                    previous.year(),
+                    previous.start().month(),
+                    format!("{header}\n{row}"),
+                )
+                .with_month(current.year(), current.start().month(), header),
+        );
+
+        let (date, curve) = client.fetch_date(last + MAX_FORWARD_DAYS.days()).unwrap();
+        assert_eq!(date, last);
+        assert_eq!(curve.get_label(Label::Mo2), Some(4.41));
+        assert_eq!(
+            client
+                .fetch_date(last + (MAX_FORWARD_DAYS + 1).days())
+                .unwrap_err(),
+            TreasuryCurveError::OutsideDateRange(
+                (last + (MAX_FORWARD_DAYS + 1).days()).to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn xml_source_gives_same_history() {
+        let fixture = Arc::new(
+            FixtureTransport::new()
+                .with_year(2023, include_str!("../tests/fixtures/yield_curve_2023.csv"))
+                .with_xml_year(2023, include_str!("../tests/fixtures/yield_curve_2023.xml")),
+        );
+        // both clients share a cache dir, the xml body must not be read back as csv
+        let dir = temp_dir("source");
+        let csv_client = TreasuryClient::builder()
+            .cache_dir(&dir)
+            .transport(fixture.clone())
+            .build();
+        let xml_client = TreasuryClient::builder()
+            .source(Source::Xml)
+            .cache_dir(&dir)
+            .transport(fixture.clone())
+            .build();
+        assert_eq!(xml_client.base_url(), DEFAULT_XML_BASE_URL);
+        assert_eq!(
+            xml_client.fetch_year(2023).unwrap(),
+            csv_client.fetch_year(2023).unwrap()
+        );
+        assert_eq!(fixture.requests().len(), 2);
+        assert!(dir.join("2023.csv").exists() && dir.join("2023.xml").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn real_curve_is_cached_apart_from_nominal() {
+        let real_csv = "Date,\"5 YR\",\"7 YR\",\"10 YR\",\"20 YR\",\"30 YR\"\n12/30/2022,1.58,1.55,1.58,1.64,1.66";
+        let fixture = Arc::new(
+            FixtureTransport::new()
+                .with_year(2022, csv_2022())
+                .with_real_year(2022, real_csv),
+        );
+        let client = TreasuryClient::builder().transport(fixture.clone()).build();
+
+        let real = client.fetch_real_year(2022).unwrap();
+        let nominal = client.fetch_year(2022).unwrap();
+        assert_eq!(real.latest().1.get_label(crate::RealLabel::Yr5), Some(1.58));
+        assert_eq!(nominal.latest().1.get_label(Label::Yr5), Some(3.99));
+        client.fetch_real_year(2022).unwrap();
+        assert_eq!(fixture.requests().len(), 2);
+    }
+
+    #[test]
+    fn bill_rates_month_from_fixture() {
+        let bills_csv = "Date,\"4 WEEKS BANK DISCOUNT\",\"4 WEEKS COUPON EQUIVALENT\"\n12/30/2022,3.88,3.96\n12/29/2022,3.82,3.90";
+        let (client, _) = fixture_client(FixtureTransport::new().with_url(
+            crate::request::period_url(
+                DEFAULT_BASE_URL,
+                BILL_RATES,
+                Period::Month(2022, time::Month::December),
+            ),
+            bills_csv,
+        ));
+        let history = client
+            .fetch_bill_rates_month(2022, time::Month::December)
+            .unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            history
+                .latest()
+                .1
+                .coupon_equivalent(crate::BillMaturity::Wk4),
+            Some(3.96)
+        );
+    }
+
+    #[test]
+    fn long_term_rates_are_csv_only() {
+        let csv = "Date,\"LT Real Average (10> Yrs)\"\n12/30/2022,1.63";
+        let fixture = Arc::new(FixtureTransport::new().with_url(
+            crate::request::period_url(DEFAULT_BASE_URL, REAL_LONG_TERM, Period::Year(2022)),
+            csv,
+        ));
+        let client = TreasuryClient::builder().transport(fixture.clone()).build();
+        let history = client.fetch_real_long_term_year(2022).unwrap();
+        assert_eq!(history.latest().1.average(), Some(1.63));
+
+        let client = TreasuryClient::builder()
+            .source(Source::Xml)
+            .transport(fixture.clone())
+            .build();
+        assert!(matches!(
+            client.fetch_long_term_year(2022),
+            Err(TreasuryCurveError::UnsupportedSource(_))
+        ));
+        assert_eq!(fixture.requests().len(), 1);
+    }
+
+    #[test]
+    fn lenient_client_keeps_new_tenors() {
+        let csv = "Date,\"1 Mo\",\"2.5 Month\",\"3 Mo\"\n12/30/2022,4.12,4.30,4.42";
+        let (client, _) = fixture_client(FixtureTransport::new().with_year(2022, csv));
+        assert_eq!(
+            client.fetch_year(2022),
+            Err(TreasuryCurveError::MissingLabel("2.5 Month".to_string()))
+        );
+        let client = TreasuryClient::builder()
+            .lenient(true)
+            .transport(FixtureTransport::new().with_year(2022, csv))
+            .build();
+        assert!(client.parse_options().lenient);
+        let (_, curve) = client.fetch_year(2022).unwrap().latest();
+        assert_eq!(curve.get_label(Label::Mo3), Some(4.42));
+        assert_eq!(curve.get(crate::Tenor::Days(70)), Some(4.30));
+    }
+
+    #[test]
+    fn maintenance_page_is_reported_and_not_cached() {
+        let url = period_url(DEFAULT_BASE_URL, YIELD_CURVE, Period::Year(2022));
+        let page = Response::ok("<!DOCTYPE html><html><body>Scheduled maintenance</body></html>");
+        let (client, fixture) =
+            fixture_client(FixtureTransport::new().with_response(url.clone(), page));
+        for _ in 0..2 {
+            assert_eq!(
+                client.fetch_year(2022),
+                Err(TreasuryCurveError::FetchFailed {
+                    attempts: 1,
+                    last: Box::new(TreasuryCurveError::UnexpectedContentType {
+                        url: url.clone(),
+                        status: 200,
+                        content_type: "text/html".to_string()
+                    })
+                })
+            );
+        }
+        assert_eq!(fixture.requests().len(), 2);
+    }
+
+    #[test]
+    fn maintenance_page_is_retried() {
+        let url = period_url(DEFAULT_BASE_URL, YIELD_CURVE, Period::Year(2022));
+        let page = Response::ok("<!DOCTYPE html><html><body>Scheduled maintenance</body></html>");
+        let fixture =
+            Arc::new(FixtureTransport::new().with_responses(url, [page, Response::ok(csv_2022())]));
+        let client = TreasuryClient::builder()
+            .retry_policy(RetryPolicy {
+                initial_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(2),
+                ..Default::default()
+            })
+            .transport(fixture.clone())
+            .build();
+
+        assert_eq!(client.fetch_year(2022).unwrap().len(), 2);
+        assert_eq!(fixture.requests().len(), 2);
+    }
+
+    #[test]
+    fn fetch_month_only_downloads_the_month() {
+        let (client, fixture) = fixture_client(
+            FixtureTransport::new()
+                .with_year(2022, csv_2022())
+                .with_year(2023, csv_2023()),
+        );
+
+        let december = client.fetch_month(2022, time::Month::December).unwrap();
+        assert_eq!(december.len(), 2);
+        assert!(client
+            .fetch_month(2022, time::Month::November)
+            .unwrap()
+            .is_empty());
+        let (date, _) = client
+            .fetch_date(Date::from_calendar_date(2023, time::Month::January, 4).unwrap())
+            .unwrap();
+        assert_eq!(
+            date,
+            Date::from_calendar_date(2023, time::Month::January, 3).unwrap()
+        );
+        assert_eq!(
+            fixture.requests(),
+            vec![
+                period_url(
+                    DEFAULT_BASE_URL,
+                    YIELD_CURVE,
+                    Period::Month(2022, time::Month::December)
+                ),
+                period_url(
+                    DEFAULT_BASE_URL,
+                    YIELD_CURVE,
+                    Period::Month(2022, time::Month::November)
+                ),
+                period_url(
+                    DEFAULT_BASE_URL,
+                    YIELD_CURVE,
+                    Period::Month(2023, time::Month::January)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn cached_year_serves_its_months() {
+        let fixture = Arc::new(FixtureTransport::new().with_year(2022, csv_2022()));
+        let client = TreasuryClient::builder()
+            .retry_policy(RetryPolicy::no_retry())
+            .transport(fixture.clone())
+            .cache(true)
+            .build();
+
+        client.fetch_year(2022).unwrap();
+        let december = client.fetch_month(2022, time::Month::December).unwrap();
+        assert_eq!(december.len(), 2);
+        assert!(client
+            .fetch_month(2022, time::Month::June)
+            .unwrap()
+            .is_empty());
+        assert_eq!(fixture.requests().len(), 1);
+    }
+}
@@ -0,0 +1,202 @@
+//! Arrow and Parquet, enabled with the `arrow` feature
+//!
+//! a [`TreasuryCurveHistory`] is a `Date` column of Date32 followed by a nullable Float64 column
+//! per [`Label`] named like the csv headers, ie. `1 Mo`, shortest first. Tenors that aren't a
+//! label and the columns kept by [`ParseOptions::lenient`](crate::ParseOptions::lenient) are
+//! left out.
+use crate::{
+    error::TreasuryCurveError,
+    treasury_curve::{tenor_header, Label, TreasuryCurve, TreasuryCurveHistory},
+    utility,
+};
+use arrow_array::{Array, ArrayRef, Date32Array, Float64Array, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::arrow::{arrow_reader::ParquetRecordBatchReaderBuilder, ArrowWriter};
+use std::{
+    io::{Read, Write},
+    sync::Arc,
+};
+
+fn schema() -> SchemaRef {
+    let fields = std::iter::once(Field::new("Date", DataType::Date32, false))
+        .chain(
+            Label::by_tenor()
+                .into_iter()
+                .map(|l| Field::new(tenor_header(l.tenor()), DataType::Float64, true)),
+        )
+        .collect::<Vec<_>>();
+    Arc::new(Schema::new(fields))
+}
+
+impl TreasuryCurveHistory {
+    /// one row per date, latest first
+    pub fn to_record_batch(&self) -> Result<RecordBatch, TreasuryCurveError> {
+        let dates = self
+            .dates()
+            .iter()
+            .map(|d| utility::days_since_epoch(*d))
+            .collect::<Vec<_>>();
+        let columns = std::iter::once(Arc::new(Date32Array::from(dates)) as ArrayRef)
+            .chain(Label::by_tenor().into_iter().map(|label| {
+                let values = self.records().iter().map(|c| c.get_label(label));
+                Arc::new(values.collect::<Float64Array>()) as ArrayRef
+            }))
+            .collect();
+        RecordBatch::try_new(schema(), columns).map_err(arrow_error)
+    }
+
+    /// write the history as a single parquet row group, ie. to a [`std::fs::File`]
+    pub fn write_parquet(&self, writer: impl Write + Send) -> Result<(), TreasuryCurveError> {
+        let mut parquet = ArrowWriter::try_new(writer, schema(), None).map_err(arrow_error)?;
+        parquet
+            .write(&self.to_record_batch()?)
+            .map_err(arrow_error)?;
+        parquet.close().map_err(arrow_error)?;
+        Ok(())
+    }
+
+    /// read a parquet file written by [`write_parquet`](TreasuryCurveHistory::write_parquet)
+    /// or any file with the same columns, label columns that are missing are `None`
+    pub fn from_parquet(mut reader: impl Read) -> Result<Self, TreasuryCurveError> {
+        let mut data = vec![];
+        reader.read_to_end(&mut data).map_err(arrow_error)?;
+        let batches = ParquetRecordBatchReaderBuilder::try_new(bytes::Bytes::from(data))
+            .and_then(|builder| builder.build())
+            .map_err(arrow_error)?;
+        let mut history = TreasuryCurveHistory::default();
+        for batch in batches {
+            history.merge(TreasuryCurveHistory::try_from(batch.map_err(arrow_error)?)?);
+        }
+        Ok(history)
+    }
+}
+
+impl TryFrom<RecordBatch> for TreasuryCurveHistory {
+    type Error = TreasuryCurveError;
+
+    /// batch with a `Date` column of Date32 and Float64 columns named like the csv headers,
+    /// a column that isn't a label fails with [`TreasuryCurveError::MissingLabel`]
+    fn try_from(batch: RecordBatch) -> Result<Self, Self::Error> {
+        let dates = batch
+            .column_by_name("Date")
+            .and_then(|c| c.as_any().downcast_ref::<Date32Array>())
+            .ok_or_else(|| TreasuryCurveError::Arrow("no Date column of Date32".to_string()))?;
+        let mut columns = vec![];
+        for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+            if field.name() == "Date" {
+                continue;
+            }
+            let label = Label::by_tenor()
+                .into_iter()
+                .find(|l| tenor_header(l.tenor()) == *field.name())
+                .ok_or_else(|| TreasuryCurveError::MissingLabel(field.name().to_string()))?;
+            let values = column
+                .as_any()
+                .downcast_ref::<Float64Array>()
+                .ok_or_else(|| {
+                    TreasuryCurveError::Arrow(format!("{} is not a Float64 column", field.name()))
+                })?;
+            columns.push((label, values));
+        }
+
+        let mut rows = vec![];
+        for row in 0..batch.num_rows() {
+            if dates.is_null(row) {
+                return Err(TreasuryCurveError::Arrow(format!("no date in row {row}")));
+            }
+            let date = utility::date_from_epoch_days(dates.value(row)).ok_or_else(|| {
+                TreasuryCurveError::Arrow(format!("date out of range in row {row}"))
+            })?;
+            let curve = TreasuryCurve::new(
+                columns
+                    .iter()
+                    .filter(|(_, values)| values.is_valid(row))
+                    .map(|(label, values)| (label.tenor(), values.value(row))),
+            );
+            rows.push((date, curve));
+        }
+        Ok(rows.into_iter().collect())
+    }
+}
+
+fn arrow_error(e: impl std::fmt::Display) -> TreasuryCurveError {
+    TreasuryCurveError::Arrow(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::treasury_curve::TreasuryCurveCsv;
+
+    fn history() -> TreasuryCurveHistory {
+        let csv = r###"Date,"1 Mo","1.5 Month","2 Mo","3 Mo","4 Mo","6 Mo","1 Yr","2 Yr","3 Yr","5 Yr","7 Yr","10 Yr","20 Yr","30 Yr"
+02/19/2025,4.32,4.32,4.33,4.34,4.32,4.29,4.24,4.26,4.29,4.37,4.46,4.55,4.81,4.77
+02/18/2025,4.31,,4.33,4.33,4.31,4.28,4.22,4.28,4.31,4.37,4.46,4.55,4.82,4.77"###;
+        let mut history =
+            TreasuryCurveHistory::try_from(TreasuryCurveCsv(csv.to_string())).unwrap();
+        history.merge(
+            TreasuryCurveHistory::try_from(TreasuryCurveCsv(
+                include_str!("../tests/fixtures/yield_curve_1999.csv").to_string(),
+            ))
+            .unwrap(),
+        );
+        history
+    }
+
+    #[test]
+    fn record_batch_round_trip() {
+        let history = history();
+        let batch = history.to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), history.len());
+        assert_eq!(batch.num_columns(), 15);
+        assert_eq!(batch.schema().field(1).name(), "1 Mo");
+        assert_eq!(batch.schema().field(2).name(), "1.5 Month");
+        // the 1.5 month wasn't published on the second date
+        assert!(batch.column(2).is_null(1));
+        assert_eq!(
+            batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<Date32Array>()
+                .unwrap()
+                .value(0),
+            20138
+        );
+        assert_eq!(TreasuryCurveHistory::try_from(batch).unwrap(), history);
+    }
+
+    #[test]
+    fn parquet_round_trip() {
+        let history = history();
+        let mut file = vec![];
+        history.write_parquet(&mut file).unwrap();
+        assert_eq!(
+            TreasuryCurveHistory::from_parquet(file.as_slice()).unwrap(),
+            history
+        );
+        assert!(matches!(
+            TreasuryCurveHistory::from_parquet(&b"Date,1 Mo"[..]),
+            Err(TreasuryCurveError::Arrow(_))
+        ));
+    }
+
+    #[test]
+    fn unknown_columns_are_rejected() {
+        let schema = Schema::new(vec![
+            Field::new("Date", DataType::Date32, false),
+            Field::new("9 Mo", DataType::Float64, true),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Date32Array::from(vec![19545])),
+                Arc::new(Float64Array::from(vec![Some(5.45)])),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            TreasuryCurveHistory::try_from(batch),
+            Err(TreasuryCurveError::MissingLabel("9 Mo".to_string()))
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! polars DataFrame, enabled with the `polars` feature
+//!
+//! - [`Layout::Wide`] is a `Date` column followed by a Float64 column per [`Label`] named like the
+//!   csv headers, ie. `1 Mo`, shortest first
+//! - [`Layout::Long`] is `Date`, `Tenor` and `Yield` columns with a row per date and label
+//!
+//! tenors that weren't published on a date are null, ie. `4 Mo` before 2022. Tenors that aren't a
+//! label and the columns kept by [`ParseOptions::lenient`](crate::ParseOptions::lenient) are
+//! left out.
+use crate::{
+    error::TreasuryCurveError,
+    treasury_curve::{tenor_header, Label, Layout, TreasuryCurve, TreasuryCurveHistory},
+    utility,
+};
+use polars::prelude::{Column, DataFrame, Int32Chunked, IntoColumn, NewChunkedArray};
+use std::collections::BTreeMap;
+use time::Date;
+
+impl TreasuryCurveHistory {
+    /// one row per date, or per date and label with [`Layout::Long`], latest first
+    pub fn to_dataframe(&self, layout: Layout) -> Result<DataFrame, TreasuryCurveError> {
+        let labels = Label::by_tenor();
+        let days = |repeat: usize| {
+            self.dates()
+                .iter()
+                .flat_map(|d| std::iter::repeat_n(utility::days_since_epoch(*d), repeat))
+                .collect::<Vec<_>>()
+        };
+        let columns = match layout {
+            Layout::Wide => std::iter::once(date_column(&days(1)))
+                .chain(labels.iter().map(|label| {
+                    let values = self
+                        .records()
+                        .iter()
+                        .map(|c| c.get_label(*label))
+                        .collect::<Vec<_>>();
+                    Column::new(tenor_header(label.tenor()).into(), values)
+                }))
+                .collect(),
+            Layout::Long => {
+                let tenors = self
+                    .records()
+                    .iter()
+                    .flat_map(|_| labels.iter().map(|l| tenor_header(l.tenor())))
+                    .collect::<Vec<_>>();
+                let yields = self
+                    .records()
+                    .iter()
+                    .flat_map(|c| labels.iter().map(|l| c.get_label(*l)))
+                    .collect::<Vec<_>>();
+                vec![
+                    date_column(&days(labels.len())),
+                    Column::new("Tenor".into(), tenors),
+                    Column::new("Yield".into(), yields),
+                ]
+            }
+        };
+        DataFrame::new(columns).map_err(polars_error)
+    }
+}
+
+impl TryFrom<DataFrame> for TreasuryCurveHistory {
+    type Error = TreasuryCurveError;
+
+    /// either layout of [`to_dataframe`](TreasuryCurveHistory::to_dataframe), a frame with
+    /// `Tenor` and `Yield` columns is read as [`Layout::Long`]. A column or tenor that isn't a
+    /// label fails with [`TreasuryCurveError::MissingLabel`]
+    fn try_from(df: DataFrame) -> Result<Self, Self::Error> {
+        let dates = df
+            .column("Date")
+            .and_then(|c| c.date())
+            .map_err(polars_error)?
+            .phys
+            .iter()
+            .map(|days| {
+                days.and_then(utility::date_from_epoch_days).ok_or_else(|| {
+                    TreasuryCurveError::Polars("missing or invalid date".to_string())
+                })
+            })
+            .collect::<Result<Vec<Date>, _>>()?;
+
+        if df.column("Tenor").is_ok() && df.column("Yield").is_ok() {
+            let tenors = df
+                .column("Tenor")
+                .and_then(|c| c.str())
+                .map_err(polars_error)?;
+            let yields = df
+                .column("Yield")
+                .and_then(|c| c.f64())
+                .map_err(polars_error)?;
+            let mut curves = BTreeMap::<Date, TreasuryCurve>::new();
+            for ((date, tenor), value) in dates.into_iter().zip(tenors).zip(yields) {
+                let label = find_label(tenor.unwrap_or_default())?;
+                let curve = curves.entry(date).or_default();
+                if let Some(value) = value {
+                    curve.insert(label.tenor(), value);
+                }
+            }
+            return Ok(curves.into_iter().collect());
+        }
+
+        let mut columns = vec![];
+        for column in df.get_columns() {
+            if column.name() == "Date" {
+                continue;
+            }
+            let label = find_label(column.name())?;
+            columns.push((label, column.f64().map_err(polars_error)?));
+        }
+        let curves =
+            (0..df.height()).map(|row| {
+                TreasuryCurve::new(columns.iter().filter_map(|(label, values)| {
+                    values.get(row).map(|value| (label.tenor(), value))
+                }))
+            });
+        Ok(dates.into_iter().zip(curves).collect())
+    }
+}
+
+fn date_column(days: &[i32]) -> Column {
+    Int32Chunked::from_slice("Date".into(), days)
+        .into_date()
+        .into_column()
+}
+
+fn find_label(name: &str) -> Result<Label, TreasuryCurveError> {
+    Label::all()
+        .iter()
+        .find(|l| tenor_header(l.tenor()) == name)
+        .copied()
+        .ok_or_else(|| TreasuryCurveError::MissingLabel(name.to_string()))
+}
+
+fn polars_error(e: impl std::fmt::Display) -> TreasuryCurveError {
+    TreasuryCurveError::Polars(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::treasury_curve::TreasuryCurveCsv;
+
+    fn history() -> TreasuryCurveHistory {
+        // 4 Mo and 1.5 Month weren't published in 2020
+        let csv = r###"Date,"1 Mo","1.5 Month","2 Mo","3 Mo","4 Mo","6 Mo","1 Yr","2 Yr","3 Yr","5 Yr","7 Yr","10 Yr","20 Yr","30 Yr"
+02/19/2025,4.32,4.32,4.33,4.34,4.32,4.29,4.24,4.26,4.29,4.37,4.46,4.55,4.81,4.77"###;
+        let mut history =
+            TreasuryCurveHistory::try_from(TreasuryCurveCsv(csv.to_string())).unwrap();
+        history.merge(
+            TreasuryCurveHistory::try_from(TreasuryCurveCsv(
+                include_str!("../tests/fixtures/yield_curve_2020.csv").to_string(),
+            ))
+            .unwrap(),
+        );
+        history
+    }
+
+    #[test]
+    fn wide_dataframe_round_trip() {
+        let history = history();
+        let df = history.to_dataframe(Layout::Wide).unwrap();
+        assert_eq!(df.shape(), (history.len(), 15));
+        let mo4 = df.column("4 Mo").unwrap().f64().unwrap();
+        assert_eq!(mo4.get(0), Some(4.32));
+        assert_eq!(mo4.null_count(), history.len() - 1);
+        assert_eq!(
+            df.column("Date").unwrap().date().unwrap().phys.get(0),
+            Some(20138)
+        );
+        assert_eq!(TreasuryCurveHistory::try_from(df).unwrap(), history);
+    }
+
+    #[test]
+    fn long_dataframe_round_trip() {
+        let history = history();
+        let df = history.to_dataframe(Layout::Long).unwrap();
+        assert_eq!(df.shape(), (history.len() * 14, 3));
+        let tenors = df.column("Tenor").unwrap().str().unwrap();
+        assert_eq!(tenors.get(0), Some("1 Mo"));
+        assert_eq!(tenors.get(1), Some("1.5 Month"));
+        // the 1.5 month is null for 2020
+        assert_eq!(df.column("Yield").unwrap().f64().unwrap().get(15), None);
+        assert_eq!(TreasuryCurveHistory::try_from(df).unwrap(), history);
+    }
+
+    #[test]
+    fn unknown_tenors_are_rejected() {
+        let df = DataFrame::new(vec![
+            date_column(&[19545]),
+            Column::new("9 Mo".into(), [Some(5.45)]),
+        ])
+        .unwrap();
+        assert_eq!(
+            TreasuryCurveHistory::try_from(df),
+            Err(TreasuryCurveError::MissingLabel("9 Mo".to_string()))
+        );
+    }
+}
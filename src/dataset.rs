@@ -0,0 +1,451 @@
+//! Shared pieces for every Treasury daily rate feed
+//!
+//! each feed is described by its record type implementing [`TreasuryDataset`], ie.
+//! [`TreasuryCurve`](crate::treasury_curve::TreasuryCurve) for the par yield curve, and is held in a
+//! [`DatasetHistory`]. [`TreasuryClient::fetch_dataset_year`](crate::TreasuryClient::fetch_dataset_year)
+//! and friends fetch, cache and retry any feed the same way.
+use crate::{
+    error::TreasuryCurveError,
+    request::{check_content, Source},
+    transport::Response,
+    treasury_curve::{date_index, parse_csv, parse_xml, TreasuryCurveCsv, TreasuryCurveXml},
+};
+use std::{
+    fmt::Debug,
+    fs::{self, File},
+    io::Read,
+    path::Path,
+};
+use time::Date;
+
+/// Record for one date of a Treasury feed along with how the feed is requested and parsed
+pub trait TreasuryDataset: Clone + Debug + PartialEq + Send + 'static {
+    /// `type` parameter of the feed, ie. `daily_treasury_yield_curve`
+    const DATA_TYPE: &'static str;
+    /// csv columns after the date, in the order passed to [`from_values`](TreasuryDataset::from_values)
+    const HEADERS: &'static [&'static str];
+    /// xml fields in the same order as `HEADERS`, empty when the feed is only published as csv
+    const XML_FIELDS: &'static [&'static str];
+
+    /// build the record from one value per header, `None` when not published
+    fn from_values(values: &[Option<f64>]) -> Self;
+
+    /// one value per header, the reverse of [`from_values`](TreasuryDataset::from_values)
+    fn values(&self) -> Vec<Option<f64>>;
+
+    /// keep values from csv columns that aren't in `HEADERS`, only used with
+    /// [`ParseOptions::lenient`], records without room for them drop the values
+    fn with_extra(self, _extra: Vec<(String, Option<f64>)>) -> Self {
+        self
+    }
+}
+
+/// Controls how csv and xml data is turned into a [`DatasetHistory`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// keep csv columns that aren't known labels instead of failing with
+    /// [`TreasuryCurveError::MissingLabel`], xml fields that aren't known are always skipped
+    pub lenient: bool,
+    /// leave out csv rows that can't be read instead of failing, each one is kept as a
+    /// [`TreasuryCurveError::ParseCsv`] in [`DatasetHistory::warnings`]
+    pub skip_invalid_rows: bool,
+}
+
+/// Hold the history of a Treasury feed
+#[derive(Debug, PartialEq)]
+/// history stored in reverse with latest at top
+pub struct DatasetHistory<D> {
+    pub(crate) records: Vec<D>,
+    pub(crate) dates: Vec<Date>,
+    pub(crate) warnings: Vec<TreasuryCurveError>,
+}
+
+impl<D> Default for DatasetHistory<D> {
+    fn default() -> Self {
+        DatasetHistory {
+            records: vec![],
+            dates: vec![],
+            warnings: vec![],
+        }
+    }
+}
+
+impl<D: TreasuryDataset> TryFrom<TreasuryCurveCsv> for DatasetHistory<D> {
+    type Error = TreasuryCurveError;
+
+    /// convert csv file with header
+    fn try_from(value: TreasuryCurveCsv) -> Result<Self, Self::Error> {
+        DatasetHistory::from_csv(&value.0, ParseOptions::default())
+    }
+}
+
+impl<D: TreasuryDataset> TryFrom<TreasuryCurveXml> for DatasetHistory<D> {
+    type Error = TreasuryCurveError;
+
+    /// convert xml feed, fields that are null or not published for the date are `None`
+    fn try_from(value: TreasuryCurveXml) -> Result<Self, Self::Error> {
+        DatasetHistory::from_xml(&value.0)
+    }
+}
+
+impl<D: TreasuryDataset> DatasetHistory<D> {
+    /// convert csv file with header
+    pub fn from_csv(csv: &str, options: ParseOptions) -> Result<Self, TreasuryCurveError> {
+        DatasetHistory::from_csv_reader(csv.as_bytes(), options)
+    }
+
+    /// load csv with header from any reader, ie. a file or stdin, with the checks made on
+    /// downloads so an empty or html body is an error. Rows are parsed as they're read, see
+    /// [`CsvRecords`](crate::CsvRecords) to go through the records without keeping them
+    pub fn from_reader(
+        reader: impl Read,
+        options: ParseOptions,
+    ) -> Result<Self, TreasuryCurveError> {
+        DatasetHistory::read_checked("reader", Source::Csv, reader, options)
+    }
+
+    // parse csv without the download checks, used once they've been made
+    fn from_csv_reader(csv: impl Read, options: ParseOptions) -> Result<Self, TreasuryCurveError> {
+        let (dates, records, warnings) = parse_csv(csv, options)?;
+        Ok(DatasetHistory {
+            records,
+            dates,
+            warnings,
+        })
+    }
+
+    /// load a csv file, or an xml feed when the extension is `xml`, with the checks made on downloads
+    pub fn from_path(
+        path: impl AsRef<Path>,
+        options: ParseOptions,
+    ) -> Result<Self, TreasuryCurveError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| read_error(path.display(), e))?;
+        let source = match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("xml") => Source::Xml,
+            _ => Source::Csv,
+        };
+        DatasetHistory::read_checked(&format!("file://{}", path.display()), source, file, options)
+    }
+
+    /// load and merge every csv and xml file in `dir`, ie. one file per year,
+    /// files are read in name order and later files replace records for the same date
+    pub fn from_dir(
+        dir: impl AsRef<Path>,
+        options: ParseOptions,
+    ) -> Result<Self, TreasuryCurveError> {
+        let dir = dir.as_ref();
+        let mut paths = vec![];
+        for entry in fs::read_dir(dir).map_err(|e| read_error(dir.display(), e))? {
+            let path = entry.map_err(|e| read_error(dir.display(), e))?.path();
+            let data_file = path.extension().is_some_and(|ext| {
+                ext.eq_ignore_ascii_case("csv") || ext.eq_ignore_ascii_case("xml")
+            });
+            if data_file && path.is_file() {
+                paths.push(path);
+            }
+        }
+        if paths.is_empty() {
+            return Err(TreasuryCurveError::ReadFile {
+                path: dir.display().to_string(),
+                message: "no csv or xml files".to_string(),
+            });
+        }
+        paths.sort();
+        let mut history = DatasetHistory::default();
+        for path in paths {
+            let file = DatasetHistory::from_path(&path, options).map_err(|e| {
+                TreasuryCurveError::InFile {
+                    path: path.display().to_string(),
+                    error: Box::new(e),
+                }
+            })?;
+            history.merge(file);
+        }
+        Ok(history)
+    }
+
+    // the first bytes are checked like a download before the rest is parsed
+    fn read_checked(
+        url: &str,
+        source: Source,
+        mut reader: impl Read,
+        options: ParseOptions,
+    ) -> Result<Self, TreasuryCurveError> {
+        let mut start = vec![];
+        (&mut reader)
+            .take(512)
+            .read_to_end(&mut start)
+            .map_err(|e| read_error(url, e))?;
+        // local data has no HTTP status so nothing to read is reported against the file
+        let start = check_content(url, source, Response::ok(start)).map_err(|e| match e {
+            TreasuryCurveError::EmptyDataset { .. } => TreasuryCurveError::ReadFile {
+                path: url.trim_start_matches("file://").to_string(),
+                message: "no data".to_string(),
+            },
+            e => e,
+        })?;
+        let mut reader = start.chain(reader);
+        match source {
+            Source::Csv => DatasetHistory::from_csv_reader(reader, options),
+            Source::Xml => {
+                let mut xml = vec![];
+                reader
+                    .read_to_end(&mut xml)
+                    .map_err(|e| read_error(url, e))?;
+                DatasetHistory::from_xml(&String::from_utf8(xml)?)
+            }
+        }
+    }
+
+    /// convert xml feed, fields that are null or not published for the date are `None`
+    pub fn from_xml(xml: &str) -> Result<Self, TreasuryCurveError> {
+        if D::XML_FIELDS.is_empty() {
+            return Err(TreasuryCurveError::UnsupportedSource(format!(
+                "{} as xml",
+                D::DATA_TYPE
+            )));
+        }
+        let (dates, records) = parse_xml(xml)?;
+        Ok(DatasetHistory {
+            records,
+            dates,
+            warnings: vec![],
+        })
+    }
+}
+
+impl<D> Extend<(Date, D)> for DatasetHistory<D> {
+    /// add records keeping latest at top, a record replaces any existing record for the same date
+    fn extend<T: IntoIterator<Item = (Date, D)>>(&mut self, iter: T) {
+        // new records go first so the stable sort keeps them ahead of existing duplicates
+        let mut zipped: Vec<(Date, D)> = iter.into_iter().collect();
+        zipped.extend(self.dates.drain(..).zip(self.records.drain(..)));
+        zipped.sort_by_key(|(date, _)| std::cmp::Reverse(*date));
+        zipped.dedup_by_key(|(date, _)| *date);
+        (self.dates, self.records) = zipped.into_iter().unzip();
+    }
+}
+
+impl<D> FromIterator<(Date, D)> for DatasetHistory<D> {
+    /// history from records in any order, a later record replaces one for the same date
+    fn from_iter<T: IntoIterator<Item = (Date, D)>>(iter: T) -> Self {
+        let mut history = DatasetHistory::default();
+        // `extend` keeps the first of each date
+        let mut rows = iter.into_iter().collect::<Vec<_>>();
+        rows.reverse();
+        history.extend(rows);
+        history
+    }
+}
+
+impl<D> IntoIterator for DatasetHistory<D> {
+    type Item = (Date, D);
+    type IntoIter = std::iter::Zip<std::vec::IntoIter<Date>, std::vec::IntoIter<D>>;
+
+    /// records with their date, latest first
+    fn into_iter(self) -> Self::IntoIter {
+        self.dates.into_iter().zip(self.records)
+    }
+}
+
+impl<D> DatasetHistory<D> {
+    /// records with their date, latest first
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (Date, &D)> + ExactSizeIterator {
+        self.dates.iter().copied().zip(&self.records)
+    }
+
+    /// dates in history, latest first
+    pub fn dates(&self) -> &[Date] {
+        &self.dates
+    }
+
+    /// records in the same order as [`dates`](DatasetHistory::dates)
+    pub fn records(&self) -> &[D] {
+        &self.records
+    }
+}
+
+impl<D: Clone> DatasetHistory<D> {
+    /// combine with another history, records in `other` replace records for the same date
+    pub fn merge(&mut self, other: DatasetHistory<D>) {
+        self.warnings.extend(other.warnings);
+        self.extend(other.dates.into_iter().zip(other.records));
+    }
+
+    /// rows left out while parsing with [`ParseOptions::skip_invalid_rows`]
+    pub fn warnings(&self) -> &[TreasuryCurveError] {
+        &self.warnings
+    }
+
+    /// only keep records from `start` to `end` inclusive
+    pub fn retain_range(&mut self, start: Date, end: Date) {
+        let (dates, records) = self
+            .dates
+            .drain(..)
+            .zip(self.records.drain(..))
+            .filter(|(date, _)| (start..=end).contains(date))
+            .unzip();
+        self.dates = dates;
+        self.records = records;
+    }
+
+    /// number of dates in history
+    pub fn len(&self) -> usize {
+        self.dates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dates.is_empty()
+    }
+
+    /// grab the latest date in history
+    ///
+    /// panics when the history is empty, ie. a month with nothing published yet, use
+    /// [`try_latest`](DatasetHistory::try_latest) when that can happen
+    pub fn latest(&self) -> (Date, D) {
+        (self.dates[0], self.records[0].clone())
+    }
+
+    /// grab the latest date in history, `None` when the history is empty
+    pub fn try_latest(&self) -> Option<(Date, D)> {
+        Some((*self.dates.first()?, self.records.first()?.clone()))
+    }
+
+    /// grab the date specified or a date prior if a record does not exist for specified date
+    /// allow 5 days after last published record
+    pub fn from_date(&self, request_date: Date) -> Result<(Date, D), TreasuryCurveError> {
+        let index = date_index(&self.dates, request_date)?;
+        Ok((self.dates[index], self.records[index].clone()))
+    }
+}
+
+fn read_error(path: impl std::fmt::Display, e: std::io::Error) -> TreasuryCurveError {
+    TreasuryCurveError::ReadFile {
+        path: path.to_string(),
+        message: e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_util::temp_dir, BillRates, RealLongTermRate, RealYieldCurve, TreasuryCurve};
+
+    #[test]
+    fn csv_only_dataset_rejects_xml() {
+        let xml = "<m:properties><d:NEW_DATE>2023-07-07T00:00:00</d:NEW_DATE></m:properties>";
+        assert_eq!(
+            DatasetHistory::<RealLongTermRate>::try_from(TreasuryCurveXml(xml.to_string())),
+            Err(TreasuryCurveError::UnsupportedSource(
+                "daily_treasury_real_long_term as xml".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn load_files_and_folders() {
+        let dir = temp_dir("archive");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("2022.csv"),
+            include_str!("../tests/fixtures/yield_curve_2022.csv"),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("2023.xml"),
+            include_str!("../tests/fixtures/yield_curve_2023.xml"),
+        )
+        .unwrap();
+        fs::write(dir.join("README.md"), "daily curves by year").unwrap();
+
+        let options = ParseOptions::default();
+        let y2022 =
+            DatasetHistory::<TreasuryCurve>::from_path(dir.join("2022.csv"), options).unwrap();
+        let y2023 =
+            DatasetHistory::<TreasuryCurve>::from_path(dir.join("2023.xml"), options).unwrap();
+        let all = DatasetHistory::<TreasuryCurve>::from_dir(&dir, options).unwrap();
+        assert_eq!(all.len(), y2022.len() + y2023.len());
+        assert_eq!(all.latest(), y2023.latest());
+
+        // files get the same checks as downloads
+        fs::write(dir.join("2021.csv"), "<!DOCTYPE html><html></html>").unwrap();
+        assert!(matches!(
+            DatasetHistory::<TreasuryCurve>::from_dir(&dir, options),
+            Err(TreasuryCurveError::InFile { path, error })
+                if path.ends_with("2021.csv")
+                    && matches!(*error, TreasuryCurveError::UnexpectedContentType { .. })
+        ));
+        assert!(matches!(
+            DatasetHistory::<TreasuryCurve>::from_path(dir.join("2020.csv"), options),
+            Err(TreasuryCurveError::ReadFile { .. })
+        ));
+        let empty = dir.join("empty");
+        fs::create_dir(&empty).unwrap();
+        assert_eq!(
+            DatasetHistory::<TreasuryCurve>::from_dir(&empty, options),
+            Err(TreasuryCurveError::ReadFile {
+                path: empty.display().to_string(),
+                message: "no csv or xml files".to_string()
+            })
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn load_from_reader() {
+        let csv = include_str!("../tests/fixtures/yield_curve_2023.csv");
+        assert_eq!(
+            DatasetHistory::<TreasuryCurve>::from_reader(csv.as_bytes(), Default::default()),
+            DatasetHistory::try_from(TreasuryCurveCsv(csv.to_string()))
+        );
+        assert_eq!(
+            DatasetHistory::<TreasuryCurve>::from_reader(&b"\n\n"[..], Default::default()),
+            Err(TreasuryCurveError::ReadFile {
+                path: "reader".to_string(),
+                message: "no data".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn iterate_and_collect() {
+        let csv = include_str!("../tests/fixtures/yield_curve_2023.csv");
+        let history =
+            DatasetHistory::<TreasuryCurve>::try_from(TreasuryCurveCsv(csv.to_string())).unwrap();
+        let (date, curve) = history.iter().next().unwrap();
+        assert_eq!((date, curve.clone()), history.latest());
+        assert_eq!(history.iter().len(), history.dates().len());
+        assert_eq!(history.records().len(), history.len());
+
+        // dates come back in any order, a later record replaces an earlier one
+        let mut rows = history
+            .iter()
+            .rev()
+            .map(|(d, c)| (d, c.clone()))
+            .collect::<Vec<_>>();
+        rows.push((date, TreasuryCurve::default()));
+        let collected = rows.into_iter().collect::<DatasetHistory<_>>();
+        assert_eq!(collected.dates(), history.dates());
+        assert_eq!(collected.latest(), (date, TreasuryCurve::default()));
+        assert_eq!(
+            collected.into_iter().nth(1),
+            history.iter().nth(1).map(|(d, c)| (d, c.clone()))
+        );
+        assert_eq!(
+            DatasetHistory::<TreasuryCurve>::default().try_latest(),
+            None
+        );
+    }
+
+    #[test]
+    fn headers_line_up_with_xml_fields() {
+        fn check<D: TreasuryDataset>() {
+            assert!(D::XML_FIELDS.is_empty() || D::XML_FIELDS.len() == D::HEADERS.len());
+        }
+        check::<TreasuryCurve>();
+        check::<RealYieldCurve>();
+        check::<BillRates>();
+        check::<RealLongTermRate>();
+    }
+}
@@ -1,3 +1,4 @@
+use crate::retry::FetchErrorKind;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -6,10 +7,75 @@ pub enum TreasuryCurveError {
     MissingLabel(String),
     #[error("no data before the year 1990 or greater than current year, using: {0}")]
     InvalidYear(i32),
+    #[error("no data for a month after the current month, using: {0}")]
+    InvalidMonth(String),
+    #[error("data is not published by the source: {0}")]
+    UnsupportedSource(String),
     #[error("requested date is outside the range of data: {0}")]
     OutsideDateRange(String),
-    #[error("fetch error - could NOT access and get data from web")]
-    FetchData(#[from] curl::Error),
+    #[error("start of date range must not be after the end: {0}")]
+    InvalidDateRange(String),
+    #[error("fetch error - could NOT access and get data from web: {message}")]
+    FetchData {
+        kind: FetchErrorKind,
+        message: String,
+    },
+    #[error("web request to {url} returned HTTP status {status}")]
+    HttpStatus { url: String, status: u32 },
+    #[error("{url} returned {content_type} instead of data (HTTP status {status})")]
+    UnexpectedContentType {
+        url: String,
+        status: u32,
+        content_type: String,
+    },
+    #[error("{url} returned no data (HTTP status {status})")]
+    EmptyDataset { url: String, status: u32 },
+    #[error("fetch failed after {attempts} attempt(s): {last}")]
+    FetchFailed {
+        attempts: u32,
+        last: Box<TreasuryCurveError>,
+    },
+    #[error("background fetch task was cancelled: {0}")]
+    TaskCancelled(String),
+    #[error("cache error on {path}: {message}")]
+    Cache { path: String, message: String },
+    #[error("{0} is not in the cache and the client is offline")]
+    NotCached(String),
+    #[error("the client was built with the cache turned off")]
+    CacheDisabled,
+    #[error("{} year(s) failed: {}", .0.len(), year_errors(.0))]
+    YearErrors(Vec<(i32, TreasuryCurveError)>),
+    #[error("could not read {path}: {message}")]
+    ReadFile { path: String, message: String },
+    #[error("{path}: {error}")]
+    InFile {
+        path: String,
+        error: Box<TreasuryCurveError>,
+    },
     #[error("trouble parsing data from web into utf8")]
     WebParseUtf8(#[from] std::string::FromUtf8Error),
+    #[error("could not read the csv at line {line} ({reason}): {row}")]
+    ParseCsv {
+        line: usize,
+        row: String,
+        reason: String,
+    },
+    #[error("could not write the csv: {0}")]
+    WriteCsv(String),
+    #[error("could not convert arrow or parquet data: {0}")]
+    Arrow(String),
+    #[error("could not convert the polars DataFrame: {0}")]
+    Polars(String),
+    #[error("sqlite store error: {0}")]
+    Store(String),
+    #[error("could not read the xml feed: {0}")]
+    WebParseXml(String),
+}
+
+fn year_errors(errors: &[(i32, TreasuryCurveError)]) -> String {
+    errors
+        .iter()
+        .map(|(year, e)| format!("{year}: {e}"))
+        .collect::<Vec<_>>()
+        .join("; ")
 }
@@ -4,20 +4,91 @@
 //!
 //!
 //! you can access API by:
-//! 1) one time fetch with [`fetch_latest`] or [`fetch_date`]
-//! 2) grab a year of data with [`fetch_year`] and then use pub functions on [`TreasuryCurveHistory`]
+//! 1) one time fetch with [`fetch_latest`] or [`fetch_date`], these only download one month
+//! 2) grab a month or year of data with [`fetch_month`] or [`fetch_year`], or several years with [`fetch_range`],
+//!    and then use pub functions on [`TreasuryCurveHistory`]
+//! 3) build a [`TreasuryClient`] to reuse the connection and cache across many calls,
+//!    the [`cache::Cache`] can be kept on disk and pre-warmed to run offline
 //!
-//! TODO: Timeout on fetching data -> timeout to retry twice and then throw error
+//! besides the nominal par yield curve the client fetches the real (TIPS) yield curve,
+//! ie. [`TreasuryClient::fetch_real_latest`] returns a [`RealYieldCurve`], the daily
+//! bill rates with [`TreasuryClient::fetch_bill_rates_latest`] and the long-term rates with
+//! [`TreasuryClient::fetch_long_term_latest`] and [`TreasuryClient::fetch_real_long_term_latest`].
+//! Every feed implements [`TreasuryDataset`] so the generic `fetch_dataset_*` methods work with
+//! any of them, ie. `client.fetch_dataset_year::<BillRates>(2023)`
+//!
+//! data comes from the csv download by default, the xml feed can be used instead with
+//! [`TreasuryClientBuilder::source`] and [`Source::Xml`]
+//!
+//! a [`TreasuryCurveHistory`] can be written back out with [`TreasuryCurveHistory::write_csv`],
+//! either in the Treasury's layout or one row per date and tenor with [`Layout::Long`]
+//!
+//! downloads go through a [`transport::Transport`], libcurl with the default `curl` feature
+//! or pure Rust with the `ureq` feature
+//!
+//! the `async` feature adds [`fetch_latest_async`], [`fetch_date_async`], [`fetch_year_async`], [`fetch_month_async`] and [`fetch_range_async`]
+//! along with matching methods on [`TreasuryClient`] for use with tokio
+//!
+//! fetches time out and retry according to [`RetryPolicy::default`], use the `_with_policy`
+//! functions or [`TreasuryClientBuilder`] to change the timeouts, number of attempts or backoff
+//!
+//! the `serde` feature implements `Serialize` and `Deserialize` for the curves, [`Tenor`],
+//! [`treasury_curve::Label`] and [`DatasetHistory`], histories are maps of ISO date to curve and
+//! curves are maps of tenor, ie. `"1 Mo"`, to yield with unpublished tenors left out
+//!
+//! the `arrow` feature converts a [`TreasuryCurveHistory`] to and from an Arrow `RecordBatch` with
+//! a Date32 column and a Float64 column per label, and writes and reads Parquet files with
+//! `TreasuryCurveHistory::write_parquet` and `TreasuryCurveHistory::from_parquet`
+//!
+//! the `polars` feature adds `TreasuryCurveHistory::to_dataframe` and `TryFrom<DataFrame>` in
+//! either [`Layout`], tenors that weren't published on a date are null
+//!
+//! the `sqlite` feature adds a `store::Store` that keeps the curve history in a SQLite database,
+//! `Store::sync` only downloads what was published since the last sync
+#[cfg(feature = "async")]
+mod async_client;
+pub mod bill_rates;
+pub mod cache;
+pub mod client;
+#[cfg(feature = "arrow")]
+mod columnar;
+#[cfg(feature = "polars")]
+mod dataframe;
+pub mod dataset;
 pub mod error;
+pub mod long_term_rates;
+pub mod period;
+pub mod real_yield_curve;
 mod request;
+pub mod retry;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "sqlite")]
+pub mod store;
+pub mod tenor;
+#[cfg(test)]
+mod test_util;
+pub mod transport;
 pub mod treasury_curve;
 mod utility;
+mod xml;
 
+pub use bill_rates::{BillMaturity, BillRate, BillRates, BillRatesHistory};
+pub use client::{TreasuryClient, TreasuryClientBuilder};
+pub use dataset::{DatasetHistory, ParseOptions, TreasuryDataset};
 use error::TreasuryCurveError;
-use request::fetch_csv_year;
-use time::Date;
+pub use long_term_rates::{
+    LongTermLabel, LongTermRate, LongTermRateHistory, RealLongTermRate, RealLongTermRateHistory,
+};
+pub use period::Period;
+pub use real_yield_curve::{RealLabel, RealYieldCurve, RealYieldCurveHistory};
+pub use request::Source;
+pub use retry::RetryPolicy;
+pub use tenor::Tenor;
+use time::{Date, Month};
+use treasury_curve::TreasuryCurve;
 use treasury_curve::TreasuryCurveHistory;
-use treasury_curve::{TreasuryCurve, TreasuryCurveCsv};
+pub use treasury_curve::{CsvRecords, Layout, WriteOptions};
 use utility::current_year;
 
 const MIN_YEAR_AVAIL: i32 = 1990;
@@ -25,36 +96,105 @@ const MAX_FORWARD_DAYS: i64 = 5;
 
 /// fetch the latest date of the Tresury Curve
 pub fn fetch_latest() -> Result<(Date, TreasuryCurve), TreasuryCurveError> {
-    Ok(fetch_year(current_year())?.latest())
+    TreasuryClient::default().fetch_latest()
 }
 
 /// fetch a specific date of the Tresury curve
 /// Defaults to the last known data point on weekend and holidays
 pub fn fetch_date(request_date: Date) -> Result<(Date, TreasuryCurve), TreasuryCurveError> {
-    // special dates are the first 4 days of the year as there may be no data because of weekends
-    // and holiday therefore need to go back and fetch the year prior as well if we get a `OutsideDateRange` Error
-    match fetch_year(request_date.year())?.from_date(request_date) {
-        Ok((date, curve)) => Ok((date, curve)),
-        Err(TreasuryCurveError::OutsideDateRange(d)) => {
-            if date_at_start_of_year(&d) {
-                Ok(fetch_year(request_date.year() - 1)?.latest())
-            } else {
-                Err(TreasuryCurveError::OutsideDateRange(d))
-            }
-        }
-        Err(e) => Err(e),
-    }
+    TreasuryClient::default().fetch_date(request_date)
 }
 
 /// fetch an entire year of Treasury curves
 pub fn fetch_year(requst_year: i32) -> Result<TreasuryCurveHistory, TreasuryCurveError> {
-    TreasuryCurveHistory::try_from(TreasuryCurveCsv(fetch_csv_year(requst_year)?))
+    TreasuryClient::default().fetch_year(requst_year)
+}
+
+/// fetch one month of Treasury curves, smaller and quicker than a year when polling for the latest curve
+pub fn fetch_month(year: i32, month: Month) -> Result<TreasuryCurveHistory, TreasuryCurveError> {
+    TreasuryClient::default().fetch_month(year, month)
+}
+
+/// fetch every curve from `start` to `end` inclusive, latest first
+pub fn fetch_range(start: Date, end: Date) -> Result<TreasuryCurveHistory, TreasuryCurveError> {
+    TreasuryClient::default().fetch_range(start, end)
+}
+
+/// same as [`fetch_latest`] using the timeouts and retries in `policy`
+pub fn fetch_latest_with_policy(
+    policy: &RetryPolicy,
+) -> Result<(Date, TreasuryCurve), TreasuryCurveError> {
+    client_with_policy(policy).fetch_latest()
 }
 
-fn date_at_start_of_year(error_str: &str) -> bool {
-    let fd = utility::date_format_error();
-    let date = Date::parse(error_str, &fd).unwrap();
-    date.to_ordinal_date().1 < 5
+/// same as [`fetch_date`] using the timeouts and retries in `policy`
+pub fn fetch_date_with_policy(
+    request_date: Date,
+    policy: &RetryPolicy,
+) -> Result<(Date, TreasuryCurve), TreasuryCurveError> {
+    client_with_policy(policy).fetch_date(request_date)
+}
+
+/// same as [`fetch_year`] using the timeouts and retries in `policy`
+pub fn fetch_year_with_policy(
+    requst_year: i32,
+    policy: &RetryPolicy,
+) -> Result<TreasuryCurveHistory, TreasuryCurveError> {
+    client_with_policy(policy).fetch_year(requst_year)
+}
+
+/// async version of [`fetch_latest`]
+#[cfg(feature = "async")]
+pub async fn fetch_latest_async() -> Result<(Date, TreasuryCurve), TreasuryCurveError> {
+    TreasuryClient::default().fetch_latest_async().await
+}
+
+/// async version of [`fetch_date`]
+#[cfg(feature = "async")]
+pub async fn fetch_date_async(
+    request_date: Date,
+) -> Result<(Date, TreasuryCurve), TreasuryCurveError> {
+    TreasuryClient::default()
+        .fetch_date_async(request_date)
+        .await
+}
+
+/// async version of [`fetch_year`]
+#[cfg(feature = "async")]
+pub async fn fetch_year_async(
+    requst_year: i32,
+) -> Result<TreasuryCurveHistory, TreasuryCurveError> {
+    TreasuryClient::default()
+        .fetch_year_async(requst_year)
+        .await
+}
+
+/// async version of [`fetch_month`]
+#[cfg(feature = "async")]
+pub async fn fetch_month_async(
+    year: i32,
+    month: Month,
+) -> Result<TreasuryCurveHistory, TreasuryCurveError> {
+    TreasuryClient::default()
+        .fetch_month_async(year, month)
+        .await
+}
+
+/// async version of [`fetch_range`]
+#[cfg(feature = "async")]
+pub async fn fetch_range_async(
+    start: Date,
+    end: Date,
+) -> Result<TreasuryCurveHistory, TreasuryCurveError> {
+    TreasuryClient::default()
+        .fetch_range_async(start, end)
+        .await
+}
+
+fn client_with_policy(policy: &RetryPolicy) -> TreasuryClient {
+    TreasuryClient::builder()
+        .retry_policy(policy.clone())
+        .build()
 }
 
 #[cfg(test)]
@@ -64,34 +204,43 @@ mod tests {
     use super::*;
     use time::ext::NumericalDuration;
 
+    fn fixture_client() -> TreasuryClient {
+        test_util::fixture_client(test_util::recorded_years()).0
+    }
+
     #[test]
     fn fetch_latest_treasury_curve() {
-        let latest = fetch_latest();
+        let latest = fixture_client().fetch_latest();
         assert!(latest.is_ok());
     }
 
     #[test]
     fn fetch_date_treasury_curve() {
+        let client = fixture_client();
         // data exists on this day
         let exist_date = Date::from_calendar_date(2023, time::Month::July, 5).unwrap();
-        assert_eq!(fetch_date(exist_date).unwrap().0, exist_date);
+        assert_eq!(client.fetch_date(exist_date).unwrap().0, exist_date);
         // data does not exist on this day Jul 2 is a weeekend -> use the day prior which is June 30
         let nonexist_date = Date::from_calendar_date(2023, time::Month::July, 2).unwrap();
         let nonexist_date_check = Date::from_calendar_date(2023, time::Month::June, 30).unwrap();
-        assert_eq!(fetch_date(nonexist_date).unwrap().0, nonexist_date_check);
+        assert_eq!(
+            client.fetch_date(nonexist_date).unwrap().0,
+            nonexist_date_check
+        );
     }
 
     #[test]
     fn fetch_date_treasury_curve_date_does_not_exist() {
+        let client = fixture_client();
         let date_check = time::OffsetDateTime::now_utc().date();
         let one_year_forward = date_check + 365.days();
         let max_days_forward = date_check + (MAX_FORWARD_DAYS + 1).days();
         assert_eq!(
-            fetch_date(one_year_forward).unwrap_err(),
+            client.fetch_date(one_year_forward).unwrap_err(),
             TreasuryCurveError::InvalidYear(one_year_forward.year())
         );
         assert_eq!(
-            fetch_date(max_days_forward).unwrap_err(),
+            client.fetch_date(max_days_forward).unwrap_err(),
             TreasuryCurveError::OutsideDateRange(max_days_forward.to_string())
         );
     }
@@ -142,9 +291,10 @@ mod tests {
         let mut date_results: Vec<Date> = vec![];
         let mut curve_results: Vec<f64> = vec![];
 
+        let client = fixture_client();
         for (i, d) in fetch_dates.iter().enumerate() {
             println!("Working on : {d}");
-            match fetch_date(*d) {
+            match client.fetch_date(*d) {
                 Ok((date, curve)) => {
                     date_results.push(date);
                     curve_results.push(curve.get_label(fetch_labels[i]).unwrap());
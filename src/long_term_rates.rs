@@ -0,0 +1,129 @@
+use crate::{
+    dataset::{DatasetHistory, TreasuryDataset},
+    request::{LONG_TERM_RATE, REAL_LONG_TERM},
+};
+
+const LONG_TERM_LENGTH: usize = 3;
+const LONG_TERM_HEADERS: [&str; LONG_TERM_LENGTH] = [
+    "LT COMPOSITE (>10 Yrs)",
+    "TREASURY 20-Yr CMT",
+    "Extrapolation Factor",
+];
+const REAL_LONG_TERM_HEADERS: [&str; 1] = ["LT Real Average (10> Yrs)"];
+
+/// Labels for the long-term rates
+#[derive(Copy, Clone)]
+pub enum LongTermLabel {
+    /// average of bonds with more than 10 years remaining
+    Composite,
+    /// 20 year constant maturity
+    Treasury20Yr,
+    /// factor used to extrapolate rates past 20 years
+    ExtrapolationFactor,
+}
+
+impl LongTermLabel {
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Captures the long-term rates for a single date
+/// order of data matches `LongTermLabel`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LongTermRate([Option<f64>; LONG_TERM_LENGTH]);
+
+impl LongTermRate {
+    pub fn get_label(&self, label: LongTermLabel) -> Option<f64> {
+        self.0[label.index()]
+    }
+}
+
+/// Captures the real long-term average for a single date
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RealLongTermRate([Option<f64>; 1]);
+
+impl RealLongTermRate {
+    /// average real rate of TIPS with more than 10 years remaining
+    pub fn average(&self) -> Option<f64> {
+        self.0[0]
+    }
+}
+
+// long-term rates are only published as csv
+impl TreasuryDataset for LongTermRate {
+    const DATA_TYPE: &'static str = LONG_TERM_RATE;
+    const HEADERS: &'static [&'static str] = &LONG_TERM_HEADERS;
+    const XML_FIELDS: &'static [&'static str] = &[];
+
+    fn from_values(values: &[Option<f64>]) -> Self {
+        LongTermRate(values.try_into().expect("one value per header"))
+    }
+
+    fn values(&self) -> Vec<Option<f64>> {
+        self.0.to_vec()
+    }
+}
+
+impl TreasuryDataset for RealLongTermRate {
+    const DATA_TYPE: &'static str = REAL_LONG_TERM;
+    const HEADERS: &'static [&'static str] = &REAL_LONG_TERM_HEADERS;
+    const XML_FIELDS: &'static [&'static str] = &[];
+
+    fn from_values(values: &[Option<f64>]) -> Self {
+        RealLongTermRate(values.try_into().expect("one value per header"))
+    }
+
+    fn values(&self) -> Vec<Option<f64>> {
+        self.0.to_vec()
+    }
+}
+
+/// Hold long-term rate history
+/// history stored in reverse with latest at top
+pub type LongTermRateHistory = DatasetHistory<LongTermRate>;
+
+/// Hold real long-term rate history
+/// history stored in reverse with latest at top
+pub type RealLongTermRateHistory = DatasetHistory<RealLongTermRate>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::treasury_curve::TreasuryCurveCsv;
+    use time::Date;
+
+    #[test]
+    fn parse_long_term_csv() {
+        let csv = r###"Date,"LT COMPOSITE (>10 Yrs)","TREASURY 20-Yr CMT","Extrapolation Factor"
+07/07/2023,4.16,4.27,N/A
+07/06/2023,4.12,4.23,N/A
+07/05/2023,4.06,4.17,N/A"###;
+        let history = LongTermRateHistory::try_from(TreasuryCurveCsv(csv.to_string())).unwrap();
+        assert_eq!(history.len(), 3);
+        let (date, rate) = history
+            .from_date(Date::from_calendar_date(2023, time::Month::July, 6).unwrap())
+            .unwrap();
+        assert_eq!(
+            date,
+            Date::from_calendar_date(2023, time::Month::July, 6).unwrap()
+        );
+        assert_eq!(rate.get_label(LongTermLabel::Composite), Some(4.12));
+        assert_eq!(rate.get_label(LongTermLabel::Treasury20Yr), Some(4.23));
+        assert_eq!(rate.get_label(LongTermLabel::ExtrapolationFactor), None);
+    }
+
+    #[test]
+    fn parse_real_long_term_csv() {
+        let csv = r###"Date,"LT Real Average (10> Yrs)"
+07/07/2023,1.78
+07/06/2023,1.75"###;
+        let history = RealLongTermRateHistory::try_from(TreasuryCurveCsv(csv.to_string())).unwrap();
+        let (date, rate) = history.latest();
+        assert_eq!(
+            date,
+            Date::from_calendar_date(2023, time::Month::July, 7).unwrap()
+        );
+        assert_eq!(rate.average(), Some(1.78));
+    }
+}
@@ -0,0 +1,133 @@
+use crate::utility::today;
+use std::{fmt, str::FromStr};
+use time::{Date, Month};
+
+/// Span of data requested from the Treasury website, a whole year or a single month
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Period {
+    Year(i32),
+    Month(i32, Month),
+}
+
+impl From<i32> for Period {
+    fn from(year: i32) -> Self {
+        Period::Year(year)
+    }
+}
+
+impl Period {
+    /// period for the month containing `date`
+    pub fn month_of(date: Date) -> Self {
+        Period::Month(date.year(), date.month())
+    }
+
+    pub fn year(&self) -> i32 {
+        match self {
+            Period::Year(year) | Period::Month(year, _) => *year,
+        }
+    }
+
+    /// first day in the period
+    pub fn start(&self) -> Date {
+        let (year, month) = match self {
+            Period::Year(year) => (*year, Month::January),
+            Period::Month(year, month) => (*year, *month),
+        };
+        Date::from_calendar_date(year, month, 1).expect("first of the month is a valid date")
+    }
+
+    /// last day in the period
+    pub fn end(&self) -> Date {
+        let (year, month) = match self {
+            Period::Year(year) => (*year, Month::December),
+            Period::Month(year, month) => (*year, *month),
+        };
+        Date::from_calendar_date(year, month, month.length(year))
+            .expect("last of the month is a valid date")
+    }
+
+    pub fn contains(&self, date: Date) -> bool {
+        (self.start()..=self.end()).contains(&date)
+    }
+
+    /// the period still receives new curves, ie. the current year or month
+    pub fn is_current(&self) -> bool {
+        self.contains(today())
+    }
+
+    /// the month before, or the year before for a year
+    pub fn previous(&self) -> Self {
+        match self {
+            Period::Year(year) => Period::Year(year - 1),
+            Period::Month(year, Month::January) => Period::Month(year - 1, Month::December),
+            Period::Month(year, month) => Period::Month(*year, month.previous()),
+        }
+    }
+
+    /// the whole period is after the current date
+    pub(crate) fn is_future(&self) -> bool {
+        self.start() > today()
+    }
+}
+
+/// `2023` for a year and `2023-07` for a month
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Period::Year(year) => write!(f, "{year}"),
+            Period::Month(year, month) => write!(f, "{year}-{:02}", u8::from(*month)),
+        }
+    }
+}
+
+impl FromStr for Period {
+    type Err = ();
+
+    /// parse the format written by `Display`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('-') {
+            None => s.parse().map(Period::Year).map_err(|_| ()),
+            Some((year, month)) if month.len() == 2 => {
+                let year = year.parse().map_err(|_| ())?;
+                let month: u8 = month.parse().map_err(|_| ())?;
+                Ok(Period::Month(year, Month::try_from(month).map_err(|_| ())?))
+            }
+            Some(_) => Err(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn period_bounds_and_previous() {
+        let feb = Period::Month(2024, Month::February);
+        assert_eq!(
+            feb.end(),
+            Date::from_calendar_date(2024, Month::February, 29).unwrap()
+        );
+        assert_eq!(feb.previous(), Period::Month(2024, Month::January));
+        assert_eq!(
+            feb.previous().previous(),
+            Period::Month(2023, Month::December)
+        );
+        assert_eq!(
+            Period::Year(2023).end(),
+            Date::from_calendar_date(2023, Month::December, 31).unwrap()
+        );
+        assert!(Period::month_of(today()).is_current());
+        assert!(!Period::Year(2020).is_current());
+    }
+
+    #[test]
+    fn period_round_trips_through_string() {
+        for period in [Period::Year(1999), Period::Month(2023, Month::July)] {
+            assert_eq!(period.to_string().parse(), Ok(period));
+        }
+        assert_eq!(Period::Month(2023, Month::July).to_string(), "2023-07");
+        assert_eq!("2023-13".parse::<Period>(), Err(()));
+        assert_eq!("notes".parse::<Period>(), Err(()));
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{
+    dataset::{DatasetHistory, TreasuryDataset},
+    request::REAL_YIELD_CURVE,
+};
+
+const REAL_CURVE_LENGTH: usize = 5;
+const REAL_CURVE_HEADERS: [&str; REAL_CURVE_LENGTH] = ["5 YR", "7 YR", "10 YR", "20 YR", "30 YR"];
+// field names used by the xml feed, same order as `REAL_CURVE_HEADERS`
+const REAL_XML_FIELDS: [&str; REAL_CURVE_LENGTH] = [
+    "TC_5YEAR",
+    "TC_7YEAR",
+    "TC_10YEAR",
+    "TC_20YEAR",
+    "TC_30YEAR",
+];
+
+/// Labels for the real yield curve
+#[derive(Copy, Clone)]
+pub enum RealLabel {
+    Yr5,
+    Yr7,
+    Yr10,
+    Yr20,
+    Yr30,
+}
+
+impl RealLabel {
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Captures one real (TIPS) yield curve for a single date
+/// order of data matches `RealLabel`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RealYieldCurve([Option<f64>; REAL_CURVE_LENGTH]);
+
+impl RealYieldCurve {
+    pub fn get_label(&self, label: RealLabel) -> Option<f64> {
+        self.0[label.index()]
+    }
+}
+
+impl TreasuryDataset for RealYieldCurve {
+    const DATA_TYPE: &'static str = REAL_YIELD_CURVE;
+    const HEADERS: &'static [&'static str] = &REAL_CURVE_HEADERS;
+    const XML_FIELDS: &'static [&'static str] = &REAL_XML_FIELDS;
+
+    fn from_values(values: &[Option<f64>]) -> Self {
+        RealYieldCurve(values.try_into().expect("one value per header"))
+    }
+
+    fn values(&self) -> Vec<Option<f64>> {
+        self.0.to_vec()
+    }
+}
+
+/// Hold real yield curve history
+/// curve history stored in reverse with latest at top
+pub type RealYieldCurveHistory = DatasetHistory<RealYieldCurve>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::TreasuryCurveError,
+        treasury_curve::{TreasuryCurveCsv, TreasuryCurveXml},
+    };
+    use time::Date;
+
+    fn real_csv_data() -> &'static str {
+        r###"Date,"5 YR","7 YR","10 YR","20 YR","30 YR"
+07/07/2023,2.21,1.99,1.82,1.75,1.80
+07/06/2023,2.24,2.00,1.81,1.72,1.77
+07/05/2023,2.12,1.89,1.71,1.66,1.72
+07/03/2023,2.07,1.84,1.64,1.60,1.66
+06/30/2023,1.97,1.76,1.58,1.56,1.62"###
+    }
+
+    #[test]
+    fn parse_real_curve_csv() {
+        let history =
+            RealYieldCurveHistory::try_from(TreasuryCurveCsv(real_csv_data().to_string())).unwrap();
+        assert_eq!(history.len(), 5);
+        let (date, curve) = history.latest();
+        assert_eq!(
+            date,
+            Date::from_calendar_date(2023, time::Month::July, 7).unwrap()
+        );
+        assert_eq!(curve.get_label(RealLabel::Yr5), Some(2.21));
+        assert_eq!(curve.get_label(RealLabel::Yr30), Some(1.80));
+
+        // nominal labels are not part of the real curve
+        let nominal = "Date,\"1 Mo\",\"5 YR\"\n07/07/2023,5.32,2.21";
+        assert_eq!(
+            RealYieldCurveHistory::try_from(TreasuryCurveCsv(nominal.to_string())),
+            Err(TreasuryCurveError::MissingLabel("1 Mo".to_string()))
+        );
+    }
+
+    #[test]
+    fn real_curve_from_date() {
+        let history =
+            RealYieldCurveHistory::try_from(TreasuryCurveCsv(real_csv_data().to_string())).unwrap();
+        // holiday falls back to the prior working day
+        let (date, curve) = history
+            .from_date(Date::from_calendar_date(2023, time::Month::July, 4).unwrap())
+            .unwrap();
+        assert_eq!(
+            date,
+            Date::from_calendar_date(2023, time::Month::July, 3).unwrap()
+        );
+        assert_eq!(curve.get_label(RealLabel::Yr10), Some(1.64));
+        assert!(history
+            .from_date(Date::from_calendar_date(2023, time::Month::June, 29).unwrap())
+            .is_err());
+        assert!(history
+            .from_date(Date::from_calendar_date(2023, time::Month::July, 13).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn parse_real_curve_xml() {
+        let xml = crate::xml::feed(
+            r#"<m:properties>
+            <d:NEW_DATE m:type="Edm.DateTime">2023-07-07T00:00:00</d:NEW_DATE>
+            <d:TC_5YEAR m:type="Edm.Double">2.21</d:TC_5YEAR>
+            <d:TC_7YEAR m:type="Edm.Double">1.99</d:TC_7YEAR>
+            <d:TC_10YEAR m:type="Edm.Double">1.82</d:TC_10YEAR>
+            <d:TC_20YEAR m:type="Edm.Double">1.75</d:TC_20YEAR>
+            <d:TC_30YEAR m:type="Edm.Double" m:null="true" />
+        </m:properties>"#,
+        );
+        let history = RealYieldCurveHistory::try_from(TreasuryCurveXml(xml)).unwrap();
+        let (_, curve) = history.latest();
+        assert_eq!(curve.get_label(RealLabel::Yr5), Some(2.21));
+        assert_eq!(curve.get_label(RealLabel::Yr30), None);
+    }
+}
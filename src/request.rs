@@ -1,38 +1,244 @@
-use crate::{current_year, error::TreasuryCurveError, MIN_YEAR_AVAIL};
-use curl::easy::Easy;
+use crate::{
+    client::{DEFAULT_BASE_URL, DEFAULT_XML_BASE_URL},
+    current_year,
+    error::TreasuryCurveError,
+    period::Period,
+    transport::Response,
+    MIN_YEAR_AVAIL,
+};
 
-/// Fetch csv data for one year
-pub(crate) fn fetch_csv_year(year: i32) -> Result<String, TreasuryCurveError> {
-    let mut easy = Easy::new();
-    let mut buffer = Vec::new();
-
-    easy.url(treasury_url(year)?.as_str()).unwrap();
-
-    let mut transfer = easy.transfer();
-    transfer.write_function(|data| {
-        buffer.extend_from_slice(data);
-        Ok(data.len())
-    })?;
-    transfer.perform()?;
-    drop(transfer);
+/// Feed the Treasury curves are downloaded from, both give the same [`TreasuryCurveHistory`](crate::treasury_curve::TreasuryCurveHistory)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Source {
+    /// csv download used by the website, the default
+    #[default]
+    Csv,
+    /// OData xml feed with explicit field names, ie. `BC_1MONTH`
+    Xml,
+}
 
-    Ok(String::from_utf8(buffer)?)
+impl Source {
+    pub fn default_base_url(&self) -> &'static str {
+        match self {
+            Source::Csv => DEFAULT_BASE_URL,
+            Source::Xml => DEFAULT_XML_BASE_URL,
+        }
+    }
 }
 
-fn treasury_url(year: i32) -> Result<String, TreasuryCurveError> {
+/// `type` of the nominal par yield curve
+pub(crate) const YIELD_CURVE: &str = "daily_treasury_yield_curve";
+/// `type` of the real (TIPS) par yield curve
+pub(crate) const REAL_YIELD_CURVE: &str = "daily_treasury_real_yield_curve";
+/// `type` of the daily bill rates
+pub(crate) const BILL_RATES: &str = "daily_treasury_bill_rates";
+/// `type` of the long-term composite and extrapolation factors
+pub(crate) const LONG_TERM_RATE: &str = "daily_treasury_long_term_rate";
+/// `type` of the real long-term average
+pub(crate) const REAL_LONG_TERM: &str = "daily_treasury_real_long_term";
+
+/// url for a period of the `data` type from `source`, checking the period is available
+pub(crate) fn treasury_url(
+    base_url: &str,
+    source: Source,
+    data: &str,
+    period: Period,
+) -> Result<String, TreasuryCurveError> {
+    let year = period.year();
     if (year < MIN_YEAR_AVAIL) || (year > current_year()) {
         return Err(TreasuryCurveError::InvalidYear(year));
     }
-    Ok(format!("https://home.treasury.gov/resource-center/data-chart-center/interest-rates/daily-treasury-rates.csv/{year}/all?type=daily_treasury_yield_curve&page&_format=csv"))
+    if period.is_future() {
+        return Err(TreasuryCurveError::InvalidMonth(period.to_string()));
+    }
+    Ok(match source {
+        Source::Csv => period_url(base_url, data, period),
+        Source::Xml => xml_url(base_url, data, period),
+    })
+}
+
+pub(crate) fn period_url(base_url: &str, data: &str, period: Period) -> String {
+    match period {
+        Period::Year(year) => {
+            format!("{base_url}/{year}/all?type={data}&page&_format=csv")
+        }
+        Period::Month(year, month) => {
+            let yyyymm = format!("{year}{:02}", u8::from(month));
+            format!("{base_url}/all/{yyyymm}?type={data}&field_tdr_date_value_month={yyyymm}&page&_format=csv")
+        }
+    }
+}
+
+pub(crate) fn xml_url(base_url: &str, data: &str, period: Period) -> String {
+    match period {
+        Period::Year(year) => {
+            format!("{base_url}?data={data}&field_tdr_date_value={year}")
+        }
+        Period::Month(year, month) => format!(
+            "{base_url}?data={data}&field_tdr_date_value_month={year}{:02}",
+            u8::from(month)
+        ),
+    }
+}
+
+/// error for responses without a success status, checked on every attempt so they can be retried
+pub(crate) fn check_status(url: &str, response: &Response) -> Result<(), TreasuryCurveError> {
+    if (200..300).contains(&response.status) {
+        Ok(())
+    } else {
+        Err(TreasuryCurveError::HttpStatus {
+            url: url.to_string(),
+            status: response.status,
+        })
+    }
+}
+
+/// body of a successful response from `source` after checking the content type and that it isn't
+/// an html page, ie. the maintenance page sent with status 200
+pub(crate) fn check_content(
+    url: &str,
+    source: Source,
+    response: Response,
+) -> Result<Vec<u8>, TreasuryCurveError> {
+    let Response {
+        status,
+        content_type,
+        body,
+    } = response;
+    let unexpected = |content_type: &str| TreasuryCurveError::UnexpectedContentType {
+        url: url.to_string(),
+        status,
+        content_type: content_type.to_string(),
+    };
+    if let Some(content_type) = &content_type {
+        if !expected_content_type(source, content_type) {
+            return Err(unexpected(content_type));
+        }
+    }
+    let start = String::from_utf8_lossy(&body[..body.len().min(512)]).to_lowercase();
+    let start = start.trim_start_matches('\u{feff}').trim_start();
+    if start.is_empty() {
+        return Err(TreasuryCurveError::EmptyDataset {
+            url: url.to_string(),
+            status,
+        });
+    }
+    if start.starts_with("<!doctype html") || start.starts_with("<html") {
+        return Err(unexpected("text/html"));
+    }
+    Ok(body)
+}
+
+// the site labels downloads inconsistently so only types that can't be the data are refused
+fn expected_content_type(source: Source, content_type: &str) -> bool {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    match mime.as_str() {
+        "text/plain" | "application/octet-stream" | "" => true,
+        _ => match source {
+            Source::Csv => mime.contains("csv") || mime == "application/vnd.ms-excel",
+            Source::Xml => mime.ends_with("xml"),
+        },
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::current_year;
+    use time::{ext::NumericalDuration, Month};
+
+    #[test]
+    fn responses_that_are_not_data() {
+        let url = "http://fixture/2023";
+        let csv = Response::ok("Date,\"1 Mo\"").with_content_type("text/csv; charset=utf-8");
+        assert_eq!(
+            check_content(url, Source::Csv, csv),
+            Ok(b"Date,\"1 Mo\"".to_vec())
+        );
+        let not_found = Response {
+            status: 404,
+            ..Response::ok("")
+        };
+        assert_eq!(
+            check_status(url, &not_found),
+            Err(TreasuryCurveError::HttpStatus {
+                url: url.to_string(),
+                status: 404
+            })
+        );
+        let html = Response::ok("<html></html>").with_content_type("text/html; charset=UTF-8");
+        assert_eq!(
+            check_content(url, Source::Csv, html),
+            Err(TreasuryCurveError::UnexpectedContentType {
+                url: url.to_string(),
+                status: 200,
+                content_type: "text/html; charset=UTF-8".to_string()
+            })
+        );
+        // maintenance page sent without a content type
+        let maintenance =
+            Response::ok("\n  <!DOCTYPE html>\n<html><body>Down for maintenance</body></html>");
+        assert!(matches!(
+            check_content(url, Source::Csv, maintenance),
+            Err(TreasuryCurveError::UnexpectedContentType { content_type, .. }) if content_type == "text/html"
+        ));
+        assert_eq!(
+            check_content(url, Source::Xml, Response::ok(" \r\n")),
+            Err(TreasuryCurveError::EmptyDataset {
+                url: url.to_string(),
+                status: 200
+            })
+        );
+        let feed = Response::ok("<?xml version=\"1.0\"?><feed/>")
+            .with_content_type("application/atom+xml");
+        assert!(check_content(url, Source::Xml, feed.clone()).is_ok());
+        assert!(check_content(url, Source::Csv, feed).is_err());
+    }
+
+    #[test]
+    fn treasury_url_for_year() {
+        assert_eq!(
+            treasury_url(DEFAULT_BASE_URL, Source::Csv, YIELD_CURVE, Period::Year(2023)).unwrap(),
+            "https://home.treasury.gov/resource-center/data-chart-center/interest-rates/daily-treasury-rates.csv/2023/all?type=daily_treasury_yield_curve&page&_format=csv"
+        );
+        assert_eq!(
+            treasury_url(
+                DEFAULT_BASE_URL,
+                Source::Csv,
+                YIELD_CURVE,
+                Period::Year(MIN_YEAR_AVAIL - 1)
+            ),
+            Err(TreasuryCurveError::InvalidYear(MIN_YEAR_AVAIL - 1))
+        );
+    }
+
+    #[test]
+    fn treasury_url_for_month() {
+        assert_eq!(
+            treasury_url(DEFAULT_BASE_URL, Source::Csv, YIELD_CURVE, Period::Month(2023, Month::July)).unwrap(),
+            "https://home.treasury.gov/resource-center/data-chart-center/interest-rates/daily-treasury-rates.csv/all/202307?type=daily_treasury_yield_curve&field_tdr_date_value_month=202307&page&_format=csv"
+        );
+        assert_eq!(
+            treasury_url(DEFAULT_BASE_URL, Source::Csv, REAL_YIELD_CURVE, Period::Month(2023, Month::July)).unwrap(),
+            "https://home.treasury.gov/resource-center/data-chart-center/interest-rates/daily-treasury-rates.csv/all/202307?type=daily_treasury_real_yield_curve&field_tdr_date_value_month=202307&page&_format=csv"
+        );
+        let next_month = Period::month_of(crate::utility::today() + 40.days());
+        assert!(treasury_url(DEFAULT_BASE_URL, Source::Csv, YIELD_CURVE, next_month).is_err());
+    }
 
     #[test]
-    fn fetch_treasury_csv_data() {
-        assert!(fetch_csv_year(current_year()).is_ok());
+    fn treasury_url_for_xml() {
+        assert_eq!(
+            treasury_url(DEFAULT_XML_BASE_URL, Source::Xml, YIELD_CURVE, Period::Year(2023)).unwrap(),
+            "https://home.treasury.gov/resource-center/data-chart-center/interest-rates/pages/xml?data=daily_treasury_yield_curve&field_tdr_date_value=2023"
+        );
+        assert_eq!(
+            treasury_url(DEFAULT_XML_BASE_URL, Source::Xml, YIELD_CURVE, Period::Month(2023, Month::July)).unwrap(),
+            "https://home.treasury.gov/resource-center/data-chart-center/interest-rates/pages/xml?data=daily_treasury_yield_curve&field_tdr_date_value_month=202307"
+        );
     }
 }
@@ -0,0 +1,232 @@
+use crate::error::TreasuryCurveError;
+use std::time::Duration;
+
+/// Broad category of a failed transfer, used to decide if a fetch is worth retrying
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FetchErrorKind {
+    /// could not resolve the host name
+    Resolve,
+    /// could not open a connection to the host
+    Connect,
+    /// connect or transfer timeout was reached
+    Timeout,
+    /// connection dropped or was reset part way through the transfer
+    Transfer,
+    /// anything else, ie. bad option, TLS certificate problems
+    Other,
+}
+
+/// Controls timeouts and retries when fetching data from the Treasury website
+///
+/// The delay before retry `n` is `initial_backoff * 2^(n-1)` capped at `max_backoff`.
+/// With `jitter` the delay is picked at random between half and all of that value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// total number of attempts including the first one
+    pub max_attempts: u32,
+    /// time allowed to open the connection
+    pub connect_timeout: Duration,
+    /// time allowed for the whole transfer
+    pub timeout: Duration,
+    /// delay before the first retry
+    pub initial_backoff: Duration,
+    /// upper bound on the delay between attempts
+    pub max_backoff: Duration,
+    /// randomize the delay between attempts
+    pub jitter: bool,
+    /// transfer errors that are retried
+    pub retry_on: Vec<FetchErrorKind>,
+    /// HTTP status codes that are retried
+    pub retry_on_status: Vec<u32>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            connect_timeout: Duration::from_secs(10),
+            timeout: Duration::from_secs(60),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+            jitter: true,
+            retry_on: vec![
+                FetchErrorKind::Resolve,
+                FetchErrorKind::Connect,
+                FetchErrorKind::Timeout,
+                FetchErrorKind::Transfer,
+            ],
+            retry_on_status: vec![408, 429, 500, 502, 503, 504],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// default timeouts but only a single attempt
+    pub fn no_retry() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// check if the error is one the policy allows to retry
+    pub fn is_retryable(&self, error: &TreasuryCurveError) -> bool {
+        match error {
+            TreasuryCurveError::FetchData { kind, .. } => self.retry_on.contains(kind),
+            TreasuryCurveError::HttpStatus { status, .. } => self.retry_on_status.contains(status),
+            // an html page in place of the data is the site's maintenance or throttling page
+            TreasuryCurveError::UnexpectedContentType { content_type, .. } => {
+                content_type == "text/html"
+            }
+            _ => false,
+        }
+    }
+
+    /// delay to wait after failed attempt number `attempt` (starting at 1)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+        if self.jitter {
+            let half = delay / 2;
+            half + half.mul_f64(fastrand::f64())
+        } else {
+            delay
+        }
+    }
+
+    /// run `op` until it succeeds, fails with an error that can't be retried or runs out of attempts
+    pub(crate) fn retry<T, F>(&self, mut op: F) -> Result<T, TreasuryCurveError>
+    where
+        F: FnMut() -> Result<T, TreasuryCurveError>,
+    {
+        let max_attempts = self.max_attempts.max(1);
+        let mut attempt = 1;
+        loop {
+            match op() {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < max_attempts && self.is_retryable(&e) => {
+                    std::thread::sleep(self.backoff(attempt));
+                    attempt += 1;
+                }
+                Err(e) => {
+                    return Err(TreasuryCurveError::FetchFailed {
+                        attempts: attempt,
+                        last: Box::new(e),
+                    })
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quick_policy() -> RetryPolicy {
+        RetryPolicy {
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(2),
+            ..Default::default()
+        }
+    }
+
+    fn status_error(status: u32) -> TreasuryCurveError {
+        TreasuryCurveError::HttpStatus {
+            url: "https://home.treasury.gov".to_string(),
+            status,
+        }
+    }
+
+    fn fetch_error(kind: FetchErrorKind) -> TreasuryCurveError {
+        TreasuryCurveError::FetchData {
+            kind,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_and_is_capped() {
+        let policy = RetryPolicy {
+            jitter: false,
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2), Duration::from_millis(1000));
+        assert_eq!(policy.backoff(4), Duration::from_millis(4000));
+        assert_eq!(policy.backoff(10), Duration::from_secs(8));
+        assert_eq!(policy.backoff(100), Duration::from_secs(8));
+    }
+
+    #[test]
+    fn backoff_with_jitter_stays_in_bounds() {
+        let policy = RetryPolicy::default();
+        for _ in 0..100 {
+            let delay = policy.backoff(2);
+            assert!(delay >= Duration::from_millis(500));
+            assert!(delay <= Duration::from_millis(1000));
+        }
+    }
+
+    #[test]
+    fn retryable_errors() {
+        let policy = RetryPolicy::default();
+        assert!(policy.is_retryable(&status_error(503)));
+        assert!(!policy.is_retryable(&status_error(404)));
+        assert!(policy.is_retryable(&fetch_error(FetchErrorKind::Timeout)));
+        assert!(!policy.is_retryable(&fetch_error(FetchErrorKind::Other)));
+        assert!(!policy.is_retryable(&TreasuryCurveError::InvalidYear(1980)));
+        let page = |content_type: &str| TreasuryCurveError::UnexpectedContentType {
+            url: "https://home.treasury.gov".to_string(),
+            status: 200,
+            content_type: content_type.to_string(),
+        };
+        assert!(policy.is_retryable(&page("text/html")));
+        assert!(!policy.is_retryable(&page("image/png")));
+    }
+
+    #[test]
+    fn retry_until_attempts_run_out() {
+        let mut calls = 0;
+        let result: Result<(), _> = quick_policy().retry(|| {
+            calls += 1;
+            Err(status_error(503))
+        });
+        assert_eq!(calls, 3);
+        assert_eq!(
+            result.unwrap_err(),
+            TreasuryCurveError::FetchFailed {
+                attempts: 3,
+                last: Box::new(status_error(503))
+            }
+        );
+    }
+
+    #[test]
+    fn retry_stops_on_success_or_non_retryable_error() {
+        let mut calls = 0;
+        let result = quick_policy().retry(|| {
+            calls += 1;
+            if calls < 2 {
+                Err(status_error(502))
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result, Ok(2));
+
+        let mut calls = 0;
+        let result: Result<(), _> = quick_policy().retry(|| {
+            calls += 1;
+            Err(status_error(404))
+        });
+        assert_eq!(calls, 1);
+        assert!(matches!(
+            result,
+            Err(TreasuryCurveError::FetchFailed { attempts: 1, .. })
+        ));
+    }
+}
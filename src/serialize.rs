@@ -0,0 +1,314 @@
+//! serde support, enabled with the `serde` feature
+//!
+//! - [`Tenor`] and [`Label`] are strings in the csv header form, ie. `"1 Mo"`, `"1.5 Month"` or
+//!   `"30 Yr"`, the short form `"6 Wk"` is read as well
+//! - [`TreasuryCurve`] is a map of tenor to yield, tenors that aren't published are left out
+//! - the other records are a map of csv header to value, ie. `"4 WEEKS BANK DISCOUNT"`
+//! - [`DatasetHistory`] is a map of ISO date, ie. `"2023-07-07"`, to record with the latest first
+use crate::{
+    bill_rates::BillRates,
+    dataset::{DatasetHistory, TreasuryDataset},
+    long_term_rates::{LongTermRate, RealLongTermRate},
+    real_yield_curve::RealYieldCurve,
+    tenor::Tenor,
+    treasury_curve::{tenor_header, Label, TreasuryCurve},
+    utility,
+};
+use serde::{
+    de::{self, MapAccess, Visitor},
+    ser::{self, SerializeMap},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{fmt, marker::PhantomData};
+use time::Date;
+
+// same name as the csv, arrow and polars columns, ie. `1.5 Month` rather than `6 Wk`
+impl Serialize for Tenor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&tenor_header(*self))
+    }
+}
+
+impl<'de> Deserialize<'de> for Tenor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let tenor = String::deserialize(deserializer)?;
+        Tenor::parse(&tenor)
+            .ok_or_else(|| de::Error::invalid_value(de::Unexpected::Str(&tenor), &"a tenor"))
+    }
+}
+
+impl Serialize for Label {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.tenor().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Label {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let tenor = Tenor::deserialize(deserializer)?;
+        Label::all()
+            .iter()
+            .find(|label| label.tenor() == tenor)
+            .copied()
+            .ok_or_else(|| de::Error::custom(format!("{tenor} is not a label")))
+    }
+}
+
+/// published points by tenor followed by columns kept with [`ParseOptions::lenient`](crate::ParseOptions::lenient)
+impl Serialize for TreasuryCurve {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.len() + self.extra().len()))?;
+        for (tenor, value) in self.points() {
+            map.serialize_entry(tenor, &Some(*value))?;
+        }
+        for (name, value) in self.extra() {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for TreasuryCurve {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct CurveVisitor;
+
+        impl<'de> Visitor<'de> for CurveVisitor {
+            type Value = TreasuryCurve;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of tenor to yield")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut curve = TreasuryCurve::default();
+                let mut extra = vec![];
+                while let Some((name, value)) = map.next_entry::<String, Option<f64>>()? {
+                    // names that are tenors become points, null points are left out
+                    match Tenor::parse(&name).filter(|t| curve.get(*t).is_none()) {
+                        Some(tenor) => {
+                            if let Some(value) = value {
+                                curve.insert(tenor, value);
+                            }
+                        }
+                        None => extra.push((name, value)),
+                    }
+                }
+                Ok(curve.with_extra(extra))
+            }
+        }
+
+        deserializer.deserialize_map(CurveVisitor)
+    }
+}
+
+// records with a fixed set of csv headers are maps of header to value
+fn serialize_record<D: TreasuryDataset, S: Serializer>(
+    record: &D,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let values = record.values();
+    let published = values.iter().filter(|v| v.is_some()).count();
+    let mut map = serializer.serialize_map(Some(published))?;
+    for (header, value) in D::HEADERS.iter().zip(values) {
+        if let Some(value) = value {
+            map.serialize_entry(header, &Some(value))?;
+        }
+    }
+    map.end()
+}
+
+fn deserialize_record<'de, D: TreasuryDataset, De: Deserializer<'de>>(
+    deserializer: De,
+) -> Result<D, De::Error> {
+    struct RecordVisitor<D>(PhantomData<D>);
+
+    impl<'de, D: TreasuryDataset> Visitor<'de> for RecordVisitor<D> {
+        type Value = D;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a map of header to value")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut values = vec![None; D::HEADERS.len()];
+            while let Some((name, value)) = map.next_entry::<String, Option<f64>>()? {
+                let index = D::HEADERS
+                    .iter()
+                    .position(|h| *h == name)
+                    .ok_or_else(|| de::Error::unknown_field(&name, D::HEADERS))?;
+                values[index] = value;
+            }
+            Ok(D::from_values(&values))
+        }
+    }
+
+    deserializer.deserialize_map(RecordVisitor(PhantomData))
+}
+
+macro_rules! record_serde {
+    ($($record:ty),*) => {$(
+        impl Serialize for $record {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serialize_record(self, serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $record {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserialize_record(deserializer)
+            }
+        }
+    )*};
+}
+
+record_serde!(RealYieldCurve, BillRates, LongTermRate, RealLongTermRate);
+
+// dates are written as `2023-07-07`
+struct IsoDate(Date);
+
+impl Serialize for IsoDate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let date = self
+            .0
+            .format(&utility::date_format_iso())
+            .map_err(ser::Error::custom)?;
+        serializer.serialize_str(&date)
+    }
+}
+
+impl<'de> Deserialize<'de> for IsoDate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let date = String::deserialize(deserializer)?;
+        Date::parse(&date, &utility::date_format_iso())
+            .map(IsoDate)
+            .map_err(|_| de::Error::invalid_value(de::Unexpected::Str(&date), &"a YYYY-MM-DD date"))
+    }
+}
+
+/// map of date to record, rows skipped while parsing aren't included
+impl<D: Serialize> Serialize for DatasetHistory<D> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.dates.len()))?;
+        for (date, record) in self.dates.iter().zip(&self.records) {
+            map.serialize_entry(&IsoDate(*date), record)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de, D: Deserialize<'de>> Deserialize<'de> for DatasetHistory<D> {
+    fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        struct HistoryVisitor<D>(PhantomData<D>);
+
+        impl<'de, D: Deserialize<'de>> Visitor<'de> for HistoryVisitor<D> {
+            type Value = DatasetHistory<D>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of date to record")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut rows = vec![];
+                while let Some((IsoDate(date), record)) = map.next_entry::<IsoDate, D>()? {
+                    rows.push((date, record));
+                }
+                // keeps latest at top whatever order the map was written in
+                let mut history = DatasetHistory::default();
+                history.extend(rows);
+                Ok(history)
+            }
+        }
+
+        deserializer.deserialize_map(HistoryVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::treasury_curve::{Label, TreasuryCurve, TreasuryCurveCsv};
+    use crate::{
+        bill_rates::BillMaturity, BillRatesHistory, ParseOptions, Tenor, TreasuryCurveHistory,
+    };
+
+    fn history() -> TreasuryCurveHistory {
+        let csv = "Date,\"1 Mo\",\"1.5 Month\",\"2 Mo\",\"9 Mo\",\"Note\",\"30 Yr\"\n\
+                   02/19/2025,4.32,4.31,,4.25,1,4.77\n\
+                   02/18/2025,4.31,,4.33,,,4.77";
+        let options = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        TreasuryCurveHistory::from_csv(csv, options).unwrap()
+    }
+
+    #[test]
+    fn json_uses_tenors_and_iso_dates() {
+        let json = serde_json::to_value(history()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "2025-02-19": {"1 Mo": 4.32, "1.5 Month": 4.31, "9 Mo": 4.25, "30 Yr": 4.77, "Note": 1.0},
+                "2025-02-18": {"1 Mo": 4.31, "2 Mo": 4.33, "30 Yr": 4.77, "Note": null}
+            })
+        );
+        assert_eq!(
+            serde_json::to_string(&Label::Mo1_5).unwrap(),
+            "\"1.5 Month\""
+        );
+        for name in ["\"1.5 Month\"", "\"6 Wk\""] {
+            assert_eq!(
+                serde_json::from_str::<Label>(name).unwrap().tenor(),
+                Tenor::Days(42)
+            );
+        }
+        // missing tenors come back as not published, never 0
+        let curve: TreasuryCurve = serde_json::from_str(r#"{"1 Mo": 4.32, "2 Mo": null}"#).unwrap();
+        assert_eq!(curve.get_label(Label::Mo1), Some(4.32));
+        assert_eq!(curve.get_label(Label::Mo2), None);
+        assert_eq!(curve.len(), 1);
+    }
+
+    #[test]
+    fn round_trip_json_msgpack_and_bincode() {
+        let history = history();
+        let json = serde_json::to_string(&history).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TreasuryCurveHistory>(&json).unwrap(),
+            history
+        );
+        let msgpack = rmp_serde::to_vec(&history).unwrap();
+        assert_eq!(
+            rmp_serde::from_slice::<TreasuryCurveHistory>(&msgpack).unwrap(),
+            history
+        );
+        let bincode = bincode::serialize(&history).unwrap();
+        assert_eq!(
+            bincode::deserialize::<TreasuryCurveHistory>(&bincode).unwrap(),
+            history
+        );
+    }
+
+    #[test]
+    fn other_records_use_csv_headers() {
+        let csv =
+            "Date,\"4 WEEKS BANK DISCOUNT\",\"4 WEEKS COUPON EQUIVALENT\"\n07/07/2023,5.17,5.28";
+        let history = BillRatesHistory::try_from(TreasuryCurveCsv(csv.to_string())).unwrap();
+        let json = serde_json::to_value(&history).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "2023-07-07": {"4 WEEKS BANK DISCOUNT": 5.17, "4 WEEKS COUPON EQUIVALENT": 5.28}
+            })
+        );
+        let back: BillRatesHistory = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            back.latest().1.coupon_equivalent(BillMaturity::Wk4),
+            Some(5.28)
+        );
+        assert_eq!(
+            bincode::deserialize::<BillRatesHistory>(&bincode::serialize(&history).unwrap())
+                .unwrap(),
+            history
+        );
+    }
+}
@@ -0,0 +1,421 @@
+//! SQLite store of the par yield curve, enabled with the `sqlite` feature
+//!
+//! every published point is a row of `(date, tenor, yield)` with ISO dates, ie. `2023-07-07`,
+//! and tenors named like the csv headers, ie. `1 Mo` or `1.5 Month`. Tenors that weren't published aren't
+//! stored and neither are the columns kept by [`ParseOptions::lenient`](crate::ParseOptions::lenient).
+//!
+//! [`Store::sync`] downloads what was published after the last synced date and
+//! [`Store::latest`], [`Store::from_date`] and [`Store::range`] are answered from the database
+//! without going to the network.
+use crate::{
+    client::TreasuryClient,
+    error::TreasuryCurveError,
+    period::Period,
+    tenor::Tenor,
+    treasury_curve::{tenor_header, TreasuryCurve, TreasuryCurveHistory},
+    utility, MAX_FORWARD_DAYS,
+};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::{fmt, path::Path};
+use time::{ext::NumericalDuration, Date, Month};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS curve (
+    date TEXT NOT NULL,
+    tenor TEXT NOT NULL,
+    yield REAL NOT NULL,
+    PRIMARY KEY (date, tenor)
+);
+CREATE TABLE IF NOT EXISTS sync (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    last_date TEXT NOT NULL
+);
+";
+
+/// Treasury curve history kept in a SQLite database
+pub struct Store {
+    conn: Connection,
+}
+
+impl fmt::Debug for Store {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Store")
+            .field("path", &self.conn.path())
+            .finish()
+    }
+}
+
+impl Store {
+    /// open or create the database at `path`
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, TreasuryCurveError> {
+        Store::with_connection(Connection::open(path).map_err(store_error)?)
+    }
+
+    /// database held in memory for the life of the value
+    pub fn in_memory() -> Result<Self, TreasuryCurveError> {
+        Store::with_connection(Connection::open_in_memory().map_err(store_error)?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, TreasuryCurveError> {
+        conn.execute_batch(SCHEMA).map_err(store_error)?;
+        Ok(Store { conn })
+    }
+
+    /// last date written by [`save`](Store::save) or [`sync`](Store::sync), `None` when empty
+    pub fn last_synced(&self) -> Result<Option<Date>, TreasuryCurveError> {
+        self.conn
+            .query_row("SELECT last_date FROM sync WHERE id = 0", [], |row| {
+                row.get::<_, String>(0)
+            })
+            .optional()
+            .map_err(store_error)?
+            .map(|date| parse_date(&date))
+            .transpose()
+    }
+
+    /// write every record in `history`, a stored date is replaced by the curve in `history`
+    ///
+    /// returns the number of dates written
+    pub fn save(&mut self, history: &TreasuryCurveHistory) -> Result<usize, TreasuryCurveError> {
+        let Some(latest) = history.dates().first().copied() else {
+            return Ok(0);
+        };
+        let last_synced = self.last_synced()?;
+        let tx = self.conn.transaction().map_err(store_error)?;
+        {
+            let mut delete = tx
+                .prepare_cached("DELETE FROM curve WHERE date = ?1")
+                .map_err(store_error)?;
+            let mut insert = tx
+                .prepare_cached("INSERT INTO curve (date, tenor, yield) VALUES (?1, ?2, ?3)")
+                .map_err(store_error)?;
+            for (date, curve) in history.iter() {
+                let date = format_date(date)?;
+                delete.execute(params![date]).map_err(store_error)?;
+                for (tenor, value) in curve.points() {
+                    insert
+                        .execute(params![date, tenor_header(*tenor), value])
+                        .map_err(store_error)?;
+                }
+            }
+        }
+        if last_synced.is_none_or(|last| latest > last) {
+            tx.execute(
+                "INSERT INTO sync (id, last_date) VALUES (0, ?1)
+                 ON CONFLICT (id) DO UPDATE SET last_date = excluded.last_date",
+                params![format_date(latest)?],
+            )
+            .map_err(store_error)?;
+        }
+        tx.commit().map_err(store_error)?;
+        Ok(history.len())
+    }
+
+    /// fetch what was published since the last synced date and save it, the month of the last
+    /// date is fetched again to pick up any revisions. An empty store starts at the current year,
+    /// use [`save`](Store::save) with [`TreasuryClient::fetch_range`] to load earlier years
+    ///
+    /// returns the number of dates written
+    pub fn sync(&mut self, client: &TreasuryClient) -> Result<usize, TreasuryCurveError> {
+        self.sync_until(client, utility::today())
+    }
+
+    fn sync_until(
+        &mut self,
+        client: &TreasuryClient,
+        today: Date,
+    ) -> Result<usize, TreasuryCurveError> {
+        let mut history = TreasuryCurveHistory::default();
+        for period in sync_periods(self.last_synced()?, today) {
+            let fetched = match period {
+                Period::Year(year) => client.fetch_year(year)?,
+                Period::Month(year, month) => client.fetch_month(year, month)?,
+            };
+            history.merge(fetched);
+        }
+        self.save(&history)
+    }
+
+    /// latest curve in the store
+    pub fn latest(&self) -> Result<(Date, TreasuryCurve), TreasuryCurveError> {
+        match self.max_date(None)? {
+            Some(date) => Ok((date, self.curve(date)?)),
+            None => Err(TreasuryCurveError::OutsideDateRange(
+                "store is empty".to_string(),
+            )),
+        }
+    }
+
+    /// curve for `request_date` or the closest date before it, allowing up to 5 days after
+    /// the latest date and nothing before the first, same as [`TreasuryCurveHistory::from_date`]
+    pub fn from_date(
+        &self,
+        request_date: Date,
+    ) -> Result<(Date, TreasuryCurve), TreasuryCurveError> {
+        let outside = || TreasuryCurveError::OutsideDateRange(request_date.to_string());
+        let latest = self.max_date(None)?.ok_or_else(outside)?;
+        let first = self.first_date()?.ok_or_else(outside)?;
+        if request_date < first || request_date > latest + MAX_FORWARD_DAYS.days() {
+            return Err(outside());
+        }
+        let date = self.max_date(Some(request_date))?.ok_or_else(outside)?;
+        Ok((date, self.curve(date)?))
+    }
+
+    /// every stored curve from `start` to `end` inclusive
+    pub fn range(
+        &self,
+        start: Date,
+        end: Date,
+    ) -> Result<TreasuryCurveHistory, TreasuryCurveError> {
+        let mut stmt = self
+            .conn
+            .prepare_cached(
+                "SELECT date, tenor, yield FROM curve WHERE date BETWEEN ?1 AND ?2 ORDER BY date",
+            )
+            .map_err(store_error)?;
+        let rows = stmt
+            .query_map(params![format_date(start)?, format_date(end)?], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, f64>(2)?,
+                ))
+            })
+            .map_err(store_error)?;
+        let mut curves: Vec<(Date, TreasuryCurve)> = vec![];
+        for row in rows {
+            let (date, tenor, value) = row.map_err(store_error)?;
+            let date = parse_date(&date)?;
+            if curves.last().map(|(d, _)| *d) != Some(date) {
+                curves.push((date, TreasuryCurve::default()));
+            }
+            if let Some((_, curve)) = curves.last_mut() {
+                curve.insert(parse_tenor(&tenor)?, value);
+            }
+        }
+        Ok(curves.into_iter().collect())
+    }
+
+    // latest stored date, on or before `before` when given
+    fn max_date(&self, before: Option<Date>) -> Result<Option<Date>, TreasuryCurveError> {
+        let before = before.map(format_date).transpose()?;
+        self.conn
+            .query_row(
+                "SELECT MAX(date) FROM curve WHERE ?1 IS NULL OR date <= ?1",
+                params![before],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .map_err(store_error)?
+            .map(|date| parse_date(&date))
+            .transpose()
+    }
+
+    fn first_date(&self) -> Result<Option<Date>, TreasuryCurveError> {
+        self.conn
+            .query_row("SELECT MIN(date) FROM curve", [], |row| {
+                row.get::<_, Option<String>>(0)
+            })
+            .map_err(store_error)?
+            .map(|date| parse_date(&date))
+            .transpose()
+    }
+
+    fn curve(&self, date: Date) -> Result<TreasuryCurve, TreasuryCurveError> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT tenor, yield FROM curve WHERE date = ?1")
+            .map_err(store_error)?;
+        let rows = stmt
+            .query_map(params![format_date(date)?], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+            })
+            .map_err(store_error)?;
+        let mut curve = TreasuryCurve::default();
+        for row in rows {
+            let (tenor, value) = row.map_err(store_error)?;
+            curve.insert(parse_tenor(&tenor)?, value);
+        }
+        Ok(curve)
+    }
+}
+
+// months left in the year of the last synced date, then whole years up to `today`
+fn sync_periods(last_synced: Option<Date>, today: Date) -> Vec<Period> {
+    let Some(last) = last_synced else {
+        return vec![Period::Year(today.year())];
+    };
+    let mut periods = vec![];
+    for year in last.year()..=today.year() {
+        if year != last.year() {
+            periods.push(Period::Year(year));
+            continue;
+        }
+        let end = if year == today.year() {
+            today.month()
+        } else {
+            Month::December
+        };
+        let mut month = last.month();
+        loop {
+            periods.push(Period::Month(year, month));
+            if month == end {
+                break;
+            }
+            month = month.next();
+        }
+    }
+    periods
+}
+
+fn format_date(date: Date) -> Result<String, TreasuryCurveError> {
+    date.format(&utility::date_format_iso())
+        .map_err(store_error)
+}
+
+fn parse_date(date: &str) -> Result<Date, TreasuryCurveError> {
+    Date::parse(date, &utility::date_format_iso())
+        .map_err(|_| TreasuryCurveError::Store(format!("invalid date {date}")))
+}
+
+fn parse_tenor(tenor: &str) -> Result<Tenor, TreasuryCurveError> {
+    Tenor::parse(tenor).ok_or_else(|| TreasuryCurveError::Store(format!("invalid tenor {tenor}")))
+}
+
+fn store_error(e: impl fmt::Display) -> TreasuryCurveError {
+    TreasuryCurveError::Store(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        retry::RetryPolicy,
+        transport::FixtureTransport,
+        treasury_curve::{Label, TreasuryCurveCsv},
+    };
+    use std::sync::Arc;
+
+    fn date(year: i32, month: Month, day: u8) -> Date {
+        Date::from_calendar_date(year, month, day).unwrap()
+    }
+
+    fn history(csv: &str) -> TreasuryCurveHistory {
+        TreasuryCurveHistory::try_from(TreasuryCurveCsv(csv.to_string())).unwrap()
+    }
+
+    #[test]
+    fn save_and_read_back() {
+        let mut store = Store::in_memory().unwrap();
+        assert_eq!(store.last_synced().unwrap(), None);
+        assert!(store.latest().is_err());
+
+        let csv = include_str!("../tests/fixtures/yield_curve_2023.csv");
+        let saved = history(csv);
+        assert_eq!(store.save(&saved).unwrap(), saved.len());
+        // saving again replaces the rows instead of adding to them
+        store.save(&saved).unwrap();
+
+        assert_eq!(store.last_synced().unwrap(), Some(saved.latest().0));
+        assert_eq!(store.latest().unwrap(), saved.latest());
+        let weekend = date(2023, Month::July, 9);
+        assert_eq!(
+            store.from_date(weekend).unwrap(),
+            saved.from_date(weekend).unwrap()
+        );
+        assert_eq!(
+            store.from_date(date(2023, Month::July, 20)),
+            Err(TreasuryCurveError::OutsideDateRange(
+                "2023-07-20".to_string()
+            ))
+        );
+        // 1 Jan is before the first stored date on 3 Jan
+        let new_year = date(2023, Month::January, 1);
+        assert_eq!(store.from_date(new_year), saved.from_date(new_year));
+        assert_eq!(
+            store.from_date(new_year),
+            Err(TreasuryCurveError::OutsideDateRange(
+                "2023-01-01".to_string()
+            ))
+        );
+        assert_eq!(
+            store
+                .range(
+                    date(2023, Month::January, 1),
+                    date(2023, Month::December, 31)
+                )
+                .unwrap(),
+            saved
+        );
+
+        // the 1.5 month is stored as a tenor like the others
+        let mut store = Store::in_memory().unwrap();
+        store
+            .save(&history(
+                "Date,\"1 Mo\",\"1.5 Month\",\"30 Yr\"\n02/19/2025,4.32,4.31,",
+            ))
+            .unwrap();
+        let (_, curve) = store.latest().unwrap();
+        assert_eq!(curve.get_label(Label::Mo1_5), Some(4.31));
+        let tenors = store
+            .conn
+            .prepare("SELECT tenor FROM curve ORDER BY yield")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(0))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(tenors, vec!["1.5 Month", "1 Mo"]);
+        assert_eq!(curve.get_label(Label::Yr30), None);
+    }
+
+    #[test]
+    fn periods_after_the_last_sync() {
+        let today = date(2023, Month::February, 14);
+        assert_eq!(sync_periods(None, today), vec![Period::Year(2023)]);
+        assert_eq!(
+            sync_periods(Some(date(2023, Month::January, 31)), today),
+            vec![
+                Period::Month(2023, Month::January),
+                Period::Month(2023, Month::February)
+            ]
+        );
+        assert_eq!(
+            sync_periods(Some(date(2021, Month::November, 30)), today),
+            vec![
+                Period::Month(2021, Month::November),
+                Period::Month(2021, Month::December),
+                Period::Year(2022),
+                Period::Year(2023)
+            ]
+        );
+    }
+
+    #[test]
+    fn sync_fetches_only_what_is_new() {
+        // the 2022 fixture only has rows from December
+        let december = include_str!("../tests/fixtures/yield_curve_2022.csv");
+        let fixture = Arc::new(
+            FixtureTransport::new()
+                .with_month(2022, Month::December, december)
+                .with_year(2023, include_str!("../tests/fixtures/yield_curve_2023.csv")),
+        );
+        let client = TreasuryClient::builder()
+            .retry_policy(RetryPolicy::no_retry())
+            .transport(fixture.clone())
+            .build();
+
+        let mut store = Store::in_memory().unwrap();
+        store.save(&history(december)).unwrap();
+        let written = store
+            .sync_until(&client, date(2023, Month::July, 10))
+            .unwrap();
+        assert_eq!(fixture.requests().len(), 2);
+        assert_eq!(written, 4 + 10);
+        assert_eq!(
+            store.last_synced().unwrap(),
+            Some(date(2023, Month::July, 7))
+        );
+        assert_eq!(store.latest().unwrap().1.get_label(Label::Mo1), Some(5.32));
+    }
+}
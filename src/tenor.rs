@@ -0,0 +1,134 @@
+use std::{cmp::Ordering, fmt};
+
+/// Time to maturity of a point on a curve
+///
+/// bills published in weeks are held in days, ie. the 6 week bill is `Days(42)`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Tenor {
+    Days(u16),
+    Months(u16),
+    Years(u16),
+}
+
+impl Tenor {
+    /// approximate number of days, used to order tenors of different units
+    pub fn days(&self) -> u32 {
+        match *self {
+            Tenor::Days(d) => d as u32,
+            Tenor::Months(m) => m as u32 * 365 / 12,
+            Tenor::Years(y) => y as u32 * 365,
+        }
+    }
+
+    /// tenor from a csv header, ie. `1 Mo`, `1.5 Month`, `52 WEEKS` or `30 YR`
+    ///
+    /// fractional months are counted as 4 weeks a month like the bill auctions, so
+    /// `1.5 Month` is the 6 week bill
+    pub fn parse(header: &str) -> Option<Tenor> {
+        let (count, unit) = header.trim().split_once(' ')?;
+        // the whole unit has to match so `1 Mo Avg` or `10 Yr Breakeven` aren't tenors
+        let unit = unit.trim().to_lowercase();
+        let whole = || count.parse::<u16>().ok();
+        match unit.as_str() {
+            "mo" | "month" | "months" => match whole() {
+                Some(m) => Some(Tenor::Months(m)),
+                None => {
+                    let weeks = count.parse::<f64>().ok()? * 4.0;
+                    if weeks.fract() != 0.0 || weeks <= 0.0 || weeks > f64::from(u16::MAX) {
+                        return None;
+                    }
+                    (weeks as u16).checked_mul(7).map(Tenor::Days)
+                }
+            },
+            "yr" | "year" | "years" => whole().map(Tenor::Years),
+            "wk" | "week" | "weeks" => whole()?.checked_mul(7).map(Tenor::Days),
+            "d" | "day" | "days" => whole().map(Tenor::Days),
+            _ => None,
+        }
+    }
+}
+
+impl Ord for Tenor {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // tie break on the unit so `Months(12)` and `Years(1)` are not equal
+        let unit = |t: &Tenor| match t {
+            Tenor::Days(_) => 0,
+            Tenor::Months(_) => 1,
+            Tenor::Years(_) => 2,
+        };
+        self.days()
+            .cmp(&other.days())
+            .then_with(|| unit(self).cmp(&unit(other)))
+    }
+}
+
+impl PartialOrd for Tenor {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// same short form as the csv headers, ie. `1 Mo`, `6 Wk` or `30 Yr`
+impl fmt::Display for Tenor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tenor::Days(d) if d % 7 == 0 => write!(f, "{} Wk", d / 7),
+            Tenor::Days(d) => write!(f, "{d} Day"),
+            Tenor::Months(m) => write!(f, "{m} Mo"),
+            Tenor::Years(y) => write!(f, "{y} Yr"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_headers() {
+        assert_eq!(Tenor::parse("1 Mo"), Some(Tenor::Months(1)));
+        assert_eq!(Tenor::parse("1.5 Month"), Some(Tenor::Days(42)));
+        assert_eq!(Tenor::parse("30 YR"), Some(Tenor::Years(30)));
+        assert_eq!(Tenor::parse("52 WEEKS"), Some(Tenor::Days(364)));
+        assert_eq!(Tenor::parse("Extrapolation Factor"), None);
+        assert_eq!(Tenor::parse("1.3 Month"), None);
+        assert_eq!(Tenor::parse("1 Mo Avg"), None);
+        assert_eq!(Tenor::parse("10 Yr Breakeven"), None);
+        assert_eq!(Tenor::parse("2 Years Real"), None);
+        assert_eq!(Tenor::parse("3 Mon"), None);
+        // too long for a tenor instead of overflowing
+        assert_eq!(Tenor::parse("10000 Wk"), None);
+        assert_eq!(Tenor::parse("0.5e4 Mo"), None);
+        assert_eq!(Tenor::parse("1e9 Mo"), None);
+        for tenor in [
+            Tenor::Months(1),
+            Tenor::Days(42),
+            Tenor::Days(3),
+            Tenor::Years(30),
+        ] {
+            assert_eq!(Tenor::parse(&tenor.to_string()), Some(tenor));
+        }
+    }
+
+    #[test]
+    fn tenors_order_by_length() {
+        let mut tenors = vec![
+            Tenor::Years(1),
+            Tenor::Months(2),
+            Tenor::Days(42),
+            Tenor::Months(1),
+            Tenor::Months(12),
+        ];
+        tenors.sort();
+        assert_eq!(
+            tenors,
+            vec![
+                Tenor::Months(1),
+                Tenor::Days(42),
+                Tenor::Months(2),
+                Tenor::Months(12),
+                Tenor::Years(1)
+            ]
+        );
+    }
+}
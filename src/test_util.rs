@@ -0,0 +1,48 @@
+//! Helpers shared by the tests of every module
+use crate::{current_year, transport::FixtureTransport, RetryPolicy, TreasuryClient};
+use std::{path::PathBuf, sync::Arc};
+
+/// client serving `fixture` without retries, the fixture is returned to check the requests made
+pub(crate) fn fixture_client(fixture: FixtureTransport) -> (TreasuryClient, Arc<FixtureTransport>) {
+    let fixture = Arc::new(fixture);
+    let client = TreasuryClient::builder()
+        .retry_policy(RetryPolicy::no_retry())
+        .transport(fixture.clone())
+        .build();
+    (client, fixture)
+}
+
+/// csv files recorded from the Treasury website, trimmed to the dates used in the tests
+/// the current year is generated with a single curve for today
+pub(crate) fn recorded_years() -> FixtureTransport {
+    let today = time::OffsetDateTime::now_utc().date();
+    let current_csv = format!(
+        "{}\n{},5.32,5.47,5.46,5.52,5.53,5.41,4.94,4.64,4.35,4.23,4.06,4.27,4.05",
+        include_str!("../tests/fixtures/yield_curve_2023.csv")
+            .lines()
+            .next()
+            .unwrap(),
+        format_args!(
+            "{:02}/{:02}/{}",
+            u8::from(today.month()),
+            today.day(),
+            today.year()
+        )
+    );
+    FixtureTransport::new()
+        .with_year(1999, include_str!("../tests/fixtures/yield_curve_1999.csv"))
+        .with_year(2011, include_str!("../tests/fixtures/yield_curve_2011.csv"))
+        .with_year(2020, include_str!("../tests/fixtures/yield_curve_2020.csv"))
+        .with_year(2022, include_str!("../tests/fixtures/yield_curve_2022.csv"))
+        .with_year(2023, include_str!("../tests/fixtures/yield_curve_2023.csv"))
+        .with_year(current_year(), current_csv)
+}
+
+/// unique path under the system temp dir, it isn't created so tests can check who creates it
+pub(crate) fn temp_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "us-treasury-yield-{name}-{}-{}",
+        std::process::id(),
+        fastrand::u64(..)
+    ))
+}
@@ -0,0 +1,137 @@
+use super::{Response, Transport, TransportConfig};
+use crate::{error::TreasuryCurveError, retry::FetchErrorKind};
+use curl::easy::Easy;
+use std::sync::Mutex;
+
+/// [`Transport`] backed by libcurl
+///
+/// keeps a pool of handles so connections are reused across requests,
+/// concurrent requests each take their own handle from the pool
+pub struct CurlTransport {
+    config: TransportConfig,
+    handles: Mutex<Vec<Easy>>,
+}
+
+impl CurlTransport {
+    pub fn new(config: TransportConfig) -> Self {
+        CurlTransport {
+            config,
+            handles: Mutex::new(vec![]),
+        }
+    }
+
+    fn get_with(&self, easy: &mut Easy, url: &str) -> Result<Response, TreasuryCurveError> {
+        let mut buffer = Vec::new();
+
+        easy.url(url)?;
+        easy.connect_timeout(self.config.connect_timeout)?;
+        easy.timeout(self.config.timeout)?;
+        // an empty proxy would turn off the http_proxy and no_proxy environment variables,
+        // every pooled handle shares the config so unset options never need clearing
+        if let Some(user_agent) = &self.config.user_agent {
+            easy.useragent(user_agent)?;
+        }
+        if let Some(proxy) = &self.config.proxy {
+            easy.proxy(proxy)?;
+        }
+
+        let mut transfer = easy.transfer();
+        transfer.write_function(|data| {
+            buffer.extend_from_slice(data);
+            Ok(data.len())
+        })?;
+        transfer.perform()?;
+        drop(transfer);
+
+        Ok(Response {
+            status: easy.response_code()?,
+            content_type: easy.content_type()?.map(str::to_string),
+            body: buffer,
+        })
+    }
+}
+
+impl Default for CurlTransport {
+    fn default() -> Self {
+        CurlTransport::new(TransportConfig::default())
+    }
+}
+
+impl Transport for CurlTransport {
+    fn get(&self, url: &str) -> Result<Response, TreasuryCurveError> {
+        let pooled = self.handles.lock().unwrap_or_else(|e| e.into_inner()).pop();
+        let mut easy = pooled.unwrap_or_else(Easy::new);
+        let result = self.get_with(&mut easy, url);
+        self.handles
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(easy);
+        result
+    }
+}
+
+impl From<&curl::Error> for FetchErrorKind {
+    fn from(e: &curl::Error) -> Self {
+        if e.is_couldnt_resolve_host() || e.is_couldnt_resolve_proxy() {
+            FetchErrorKind::Resolve
+        } else if e.is_couldnt_connect() || e.is_ssl_connect_error() {
+            FetchErrorKind::Connect
+        } else if e.is_operation_timedout() {
+            FetchErrorKind::Timeout
+        } else if e.is_got_nothing()
+            || e.is_send_error()
+            || e.is_recv_error()
+            || e.is_partial_file()
+            || e.is_http2_error()
+            || e.is_http2_stream_error()
+        {
+            FetchErrorKind::Transfer
+        } else {
+            FetchErrorKind::Other
+        }
+    }
+}
+
+impl From<curl::Error> for TreasuryCurveError {
+    fn from(e: curl::Error) -> Self {
+        TreasuryCurveError::FetchData {
+            kind: FetchErrorKind::from(&e),
+            message: e.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::current_year;
+
+    #[test]
+    fn curl_errors_are_classified() {
+        assert_eq!(
+            FetchErrorKind::from(&curl::Error::new(28)),
+            FetchErrorKind::Timeout
+        );
+        assert_eq!(
+            FetchErrorKind::from(&curl::Error::new(6)),
+            FetchErrorKind::Resolve
+        );
+        assert_eq!(
+            FetchErrorKind::from(&curl::Error::new(3)),
+            FetchErrorKind::Other
+        );
+    }
+
+    #[test]
+    #[ignore = "fetches from the live Treasury website"]
+    fn fetch_treasury_csv_data() {
+        let url = crate::request::treasury_url(
+            crate::client::DEFAULT_BASE_URL,
+            crate::Source::Csv,
+            crate::request::YIELD_CURVE,
+            crate::Period::Year(current_year()),
+        );
+        let response = CurlTransport::default().get(&url.unwrap()).unwrap();
+        assert_eq!(response.status, 200);
+    }
+}
@@ -0,0 +1,169 @@
+use super::{Response, Transport};
+use crate::{
+    client::{DEFAULT_BASE_URL, DEFAULT_XML_BASE_URL},
+    error::TreasuryCurveError,
+    period::Period,
+    request::{period_url, xml_url, REAL_YIELD_CURVE, YIELD_CURVE},
+};
+use std::{collections::HashMap, sync::Mutex};
+use time::Month;
+
+/// [`Transport`] that serves responses from memory, ie. recorded csv files
+///
+/// urls that were not added return HTTP status 404,
+/// every requested url is recorded and available from [`FixtureTransport::requests`]
+#[derive(Debug, Default)]
+pub struct FixtureTransport {
+    responses: HashMap<String, Vec<Response>>,
+    requests: Mutex<Vec<String>>,
+}
+
+impl FixtureTransport {
+    pub fn new() -> Self {
+        FixtureTransport::default()
+    }
+
+    /// respond to `url` with `body` and status 200
+    pub fn with_url(self, url: impl Into<String>, body: impl Into<Vec<u8>>) -> Self {
+        self.with_response(url, Response::ok(body))
+    }
+
+    /// respond to `url` with `response`, ie. to serve an error page
+    pub fn with_response(self, url: impl Into<String>, response: Response) -> Self {
+        self.with_responses(url, [response])
+    }
+
+    /// respond to each request for `url` with the next of `responses`, the last one is repeated
+    /// once they run out, ie. a maintenance page followed by the data
+    pub fn with_responses(
+        mut self,
+        url: impl Into<String>,
+        responses: impl IntoIterator<Item = Response>,
+    ) -> Self {
+        self.responses
+            .insert(url.into(), responses.into_iter().collect());
+        self
+    }
+
+    /// respond to the request for `year` made against [`DEFAULT_BASE_URL`]
+    ///
+    /// the requests for each month of the year are answered with the header and
+    /// the rows of `csv` dated in that month
+    pub fn with_year(mut self, year: i32, csv: impl AsRef<str>) -> Self {
+        let csv = csv.as_ref();
+        let mut lines = csv.lines();
+        let header = lines.next().unwrap_or_default();
+        let rows: Vec<&str> = lines.collect();
+        for month in 1..=12u8 {
+            let prefix = format!("{month:02}/");
+            let month_csv: Vec<&str> = std::iter::once(header)
+                .chain(rows.iter().copied().filter(|r| r.starts_with(&prefix)))
+                .collect();
+            let month = Month::try_from(month).expect("months 1 to 12 are valid");
+            self = self.with_month(year, month, month_csv.join("\n"));
+        }
+        self.with_url(
+            period_url(DEFAULT_BASE_URL, YIELD_CURVE, Period::Year(year)),
+            csv,
+        )
+    }
+
+    /// respond to the request for one month made against [`DEFAULT_BASE_URL`]
+    pub fn with_month(self, year: i32, month: Month, body: impl Into<Vec<u8>>) -> Self {
+        self.with_url(
+            period_url(DEFAULT_BASE_URL, YIELD_CURVE, Period::Month(year, month)),
+            body,
+        )
+    }
+
+    /// respond to the xml feed request for `year` made against [`DEFAULT_XML_BASE_URL`]
+    pub fn with_xml_year(self, year: i32, body: impl Into<Vec<u8>>) -> Self {
+        self.with_url(
+            xml_url(DEFAULT_XML_BASE_URL, YIELD_CURVE, Period::Year(year)),
+            body,
+        )
+    }
+
+    /// respond to the real yield curve request for `year` made against [`DEFAULT_BASE_URL`]
+    pub fn with_real_year(self, year: i32, body: impl Into<Vec<u8>>) -> Self {
+        self.with_url(
+            period_url(DEFAULT_BASE_URL, REAL_YIELD_CURVE, Period::Year(year)),
+            body,
+        )
+    }
+
+    /// urls requested so far, in order
+    pub fn requests(&self) -> Vec<String> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+impl Transport for FixtureTransport {
+    fn get(&self, url: &str) -> Result<Response, TreasuryCurveError> {
+        let mut requests = self.requests.lock().unwrap_or_else(|e| e.into_inner());
+        let served = requests.iter().filter(|r| *r == url).count();
+        requests.push(url.to_string());
+        let response = self
+            .responses
+            .get(url)
+            .and_then(|responses| responses.get(served).or(responses.last()));
+        Ok(response.cloned().unwrap_or(Response {
+            status: 404,
+            content_type: Some("text/html".to_string()),
+            body: b"<html><body>Page not found</body></html>".to_vec(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn year_is_split_into_months() {
+        let transport = FixtureTransport::new().with_year(
+            2023,
+            "Date,\"1 Mo\"\n07/03/2023,5.27\n06/30/2023,5.24\n06/29/2023,5.25",
+        );
+        let june = period_url(
+            DEFAULT_BASE_URL,
+            YIELD_CURVE,
+            Period::Month(2023, Month::June),
+        );
+        assert_eq!(
+            transport.get(&june).unwrap().body,
+            b"Date,\"1 Mo\"\n06/30/2023,5.24\n06/29/2023,5.25"
+        );
+        let may = period_url(
+            DEFAULT_BASE_URL,
+            YIELD_CURVE,
+            Period::Month(2023, Month::May),
+        );
+        assert_eq!(transport.get(&may).unwrap().body, b"Date,\"1 Mo\"");
+    }
+
+    #[test]
+    fn serves_and_records_requests() {
+        let transport = FixtureTransport::new().with_url("http://fixture/a", "a");
+        assert_eq!(transport.get("http://fixture/a"), Ok(Response::ok("a")));
+        assert_eq!(transport.get("http://fixture/b").unwrap().status, 404);
+        assert_eq!(
+            transport.requests(),
+            vec![
+                "http://fixture/a".to_string(),
+                "http://fixture/b".to_string()
+            ]
+        );
+
+        let transport = FixtureTransport::new().with_responses(
+            "http://fixture/a",
+            [Response::ok("first"), Response::ok("second")],
+        );
+        assert_eq!(transport.get("http://fixture/a").unwrap().body, b"first");
+        assert_eq!(transport.get("http://fixture/a").unwrap().body, b"second");
+        assert_eq!(transport.get("http://fixture/a").unwrap().body, b"second");
+    }
+}
@@ -0,0 +1,108 @@
+//! HTTP transports used by [`TreasuryClient`](crate::TreasuryClient) to download data
+//!
+//! - [`CurlTransport`] wraps libcurl, enabled with the `curl` feature (default)
+//! - [`UreqTransport`] is pure Rust, enabled with the `ureq` feature
+//! - [`FixtureTransport`] serves canned responses from memory for offline use and tests
+#[cfg(feature = "curl")]
+mod curl_transport;
+mod fixture;
+#[cfg(feature = "ureq")]
+mod ureq_transport;
+
+#[cfg(feature = "curl")]
+pub use curl_transport::CurlTransport;
+pub use fixture::FixtureTransport;
+#[cfg(feature = "ureq")]
+pub use ureq_transport::UreqTransport;
+
+use crate::error::TreasuryCurveError;
+use std::{sync::Arc, time::Duration};
+
+/// Response to a request, whatever the status code
+#[derive(Clone, Debug, PartialEq)]
+pub struct Response {
+    pub status: u32,
+    /// `Content-Type` header if one was sent, ie. `text/csv; charset=utf-8`
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// status 200 response without a content type
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Response {
+            status: 200,
+            content_type: None,
+            body: body.into(),
+        }
+    }
+
+    pub fn with_content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+}
+
+/// Downloads a url
+///
+/// implementations return [`TreasuryCurveError::FetchData`] for transfer failures so the
+/// [`RetryPolicy`](crate::RetryPolicy) can decide what to retry, responses with an error
+/// status are returned as is and checked by the client
+pub trait Transport: Send + Sync {
+    fn get(&self, url: &str) -> Result<Response, TreasuryCurveError>;
+}
+
+/// share one transport, ie. to inspect a [`FixtureTransport`] after handing it to a client
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    fn get(&self, url: &str) -> Result<Response, TreasuryCurveError> {
+        (**self).get(url)
+    }
+}
+
+/// Connection settings shared by the network transports
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransportConfig {
+    pub user_agent: Option<String>,
+    pub proxy: Option<String>,
+    pub connect_timeout: Duration,
+    pub timeout: Duration,
+}
+
+impl Default for TransportConfig {
+    fn default() -> Self {
+        let policy = crate::RetryPolicy::default();
+        TransportConfig {
+            user_agent: None,
+            proxy: None,
+            connect_timeout: policy.connect_timeout,
+            timeout: policy.timeout,
+        }
+    }
+}
+
+/// transport picked by the client when none is given, curl is preferred over ureq
+pub(crate) fn default_transport(config: TransportConfig) -> Arc<dyn Transport> {
+    #[cfg(feature = "curl")]
+    return Arc::new(CurlTransport::new(config));
+    #[cfg(all(feature = "ureq", not(feature = "curl")))]
+    return Arc::new(UreqTransport::new(config));
+    #[cfg(not(any(feature = "curl", feature = "ureq")))]
+    {
+        let _ = config;
+        Arc::new(NoTransport)
+    }
+}
+
+/// stand in when the crate is built without any network transport
+#[cfg(not(any(feature = "curl", feature = "ureq")))]
+struct NoTransport;
+
+#[cfg(not(any(feature = "curl", feature = "ureq")))]
+impl Transport for NoTransport {
+    fn get(&self, _url: &str) -> Result<Response, TreasuryCurveError> {
+        Err(TreasuryCurveError::FetchData {
+            kind: crate::retry::FetchErrorKind::Other,
+            message: "no transport available, enable the `curl` or `ureq` feature".to_string(),
+        })
+    }
+}
@@ -0,0 +1,124 @@
+use super::{Response, Transport, TransportConfig};
+use crate::{error::TreasuryCurveError, retry::FetchErrorKind};
+use std::io::Read;
+
+/// Pure Rust [`Transport`] backed by ureq
+///
+/// the agent keeps a pool of connections that are reused across requests
+pub struct UreqTransport {
+    agent: ureq::Agent,
+    proxy_error: Option<String>,
+}
+
+impl UreqTransport {
+    pub fn new(config: TransportConfig) -> Self {
+        let mut builder = ureq::AgentBuilder::new()
+            .timeout_connect(config.connect_timeout)
+            .timeout(config.timeout);
+        if let Some(user_agent) = &config.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        // a bad proxy is reported on the first request to match the curl transport
+        let mut proxy_error = None;
+        if let Some(proxy) = &config.proxy {
+            match ureq::Proxy::new(proxy) {
+                Ok(proxy) => builder = builder.proxy(proxy),
+                Err(e) => proxy_error = Some(e.to_string()),
+            }
+        }
+        UreqTransport {
+            agent: builder.build(),
+            proxy_error,
+        }
+    }
+}
+
+impl Default for UreqTransport {
+    fn default() -> Self {
+        UreqTransport::new(TransportConfig::default())
+    }
+}
+
+impl Transport for UreqTransport {
+    fn get(&self, url: &str) -> Result<Response, TreasuryCurveError> {
+        if let Some(message) = &self.proxy_error {
+            return Err(TreasuryCurveError::FetchData {
+                kind: FetchErrorKind::Other,
+                message: message.clone(),
+            });
+        }
+        let response = match self.agent.get(url).call() {
+            // error status codes still come with a response
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(ureq::Error::Transport(t)) => return Err(transport_error(&t)),
+        };
+
+        let status = response.status().into();
+        let content_type = response.header("Content-Type").map(str::to_string);
+        let mut buffer = Vec::new();
+        response
+            .into_reader()
+            .read_to_end(&mut buffer)
+            .map_err(|e| TreasuryCurveError::FetchData {
+                kind: io_error_kind(&e),
+                message: e.to_string(),
+            })?;
+        Ok(Response {
+            status,
+            content_type,
+            body: buffer,
+        })
+    }
+}
+
+fn transport_error(t: &ureq::Transport) -> TreasuryCurveError {
+    let kind = match t.kind() {
+        ureq::ErrorKind::Dns => FetchErrorKind::Resolve,
+        ureq::ErrorKind::ConnectionFailed | ureq::ErrorKind::ProxyConnect => {
+            FetchErrorKind::Connect
+        }
+        ureq::ErrorKind::Io => std::error::Error::source(t)
+            .and_then(|e| e.downcast_ref::<std::io::Error>())
+            .map_or(FetchErrorKind::Transfer, io_error_kind),
+        _ => FetchErrorKind::Other,
+    };
+    TreasuryCurveError::FetchData {
+        kind,
+        message: t.to_string(),
+    }
+}
+
+fn io_error_kind(e: &std::io::Error) -> FetchErrorKind {
+    match e.kind() {
+        std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock => FetchErrorKind::Timeout,
+        _ => FetchErrorKind::Transfer,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn io_errors_are_classified() {
+        let timeout = std::io::Error::new(std::io::ErrorKind::TimedOut, "timed out");
+        assert_eq!(io_error_kind(&timeout), FetchErrorKind::Timeout);
+        let reset = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset");
+        assert_eq!(io_error_kind(&reset), FetchErrorKind::Transfer);
+    }
+
+    #[test]
+    fn bad_proxy_is_reported_on_request() {
+        let transport = UreqTransport::new(TransportConfig {
+            proxy: Some("ftp://proxy.local:21".to_string()),
+            ..Default::default()
+        });
+        assert!(matches!(
+            transport.get("http://127.0.0.1:9"),
+            Err(TreasuryCurveError::FetchData {
+                kind: FetchErrorKind::Other,
+                ..
+            })
+        ));
+    }
+}
@@ -1,12 +1,53 @@
-use crate::{error::TreasuryCurveError, utility, MAX_FORWARD_DAYS};
+use crate::{
+    dataset::{DatasetHistory, ParseOptions, TreasuryDataset},
+    error::TreasuryCurveError,
+    request::YIELD_CURVE,
+    tenor::Tenor,
+    utility, xml, MAX_FORWARD_DAYS,
+};
+use csv::{ReaderBuilder, StringRecord, StringRecordsIntoIter, Trim, WriterBuilder};
+use std::{
+    io::{Read, Write},
+    marker::PhantomData,
+};
 use time::{ext::NumericalDuration, Date};
 
 // implicit discriminator (starts at 0)
-const CURVE_LENGTH: usize = 13;
+const CURVE_LENGTH: usize = 14;
 const CURVE_HEADERS: [&str; CURVE_LENGTH] = [
-    "1 Mo", "2 Mo", "3 Mo", "4 Mo", "6 Mo", "1 Yr", "2 Yr", "3 Yr", "5 Yr", "7 Yr", "10 Yr",
-    "20 Yr", "30 Yr",
+    "1 Mo",
+    "2 Mo",
+    "3 Mo",
+    "4 Mo",
+    "6 Mo",
+    "1 Yr",
+    "2 Yr",
+    "3 Yr",
+    "5 Yr",
+    "7 Yr",
+    "10 Yr",
+    "20 Yr",
+    "30 Yr",
+    "1.5 Month",
 ];
+// field names used by the xml feed, same order as `CURVE_HEADERS`
+const XML_FIELDS: [&str; CURVE_LENGTH] = [
+    "BC_1MONTH",
+    "BC_2MONTH",
+    "BC_3MONTH",
+    "BC_4MONTH",
+    "BC_6MONTH",
+    "BC_1YEAR",
+    "BC_2YEAR",
+    "BC_3YEAR",
+    "BC_5YEAR",
+    "BC_7YEAR",
+    "BC_10YEAR",
+    "BC_20YEAR",
+    "BC_30YEAR",
+    "BC_1_5MONTH",
+];
+const XML_DATE_FIELD: &str = "NEW_DATE";
 
 /// Labels for the Treasury curve
 #[derive(Copy, Clone)]
@@ -24,152 +65,611 @@ pub enum Label {
     Yr10,
     Yr20,
     Yr30,
+    /// 1.5 month (6 week), published since 2025 and kept last so earlier labels keep their index
+    Mo1_5,
 }
 
+// same order as `CURVE_HEADERS`
+const LABELS: [Label; CURVE_LENGTH] = [
+    Label::Mo1,
+    Label::Mo2,
+    Label::Mo3,
+    Label::Mo4,
+    Label::Mo6,
+    Label::Yr1,
+    Label::Yr2,
+    Label::Yr3,
+    Label::Yr5,
+    Label::Yr7,
+    Label::Yr10,
+    Label::Yr20,
+    Label::Yr30,
+    Label::Mo1_5,
+];
+
 impl Label {
     pub fn index(&self) -> usize {
         *self as usize
     }
+
+    pub fn tenor(&self) -> Tenor {
+        match self {
+            Label::Mo1 => Tenor::Months(1),
+            Label::Mo2 => Tenor::Months(2),
+            Label::Mo3 => Tenor::Months(3),
+            Label::Mo4 => Tenor::Months(4),
+            Label::Mo6 => Tenor::Months(6),
+            Label::Yr1 => Tenor::Years(1),
+            Label::Yr2 => Tenor::Years(2),
+            Label::Yr3 => Tenor::Years(3),
+            Label::Yr5 => Tenor::Years(5),
+            Label::Yr7 => Tenor::Years(7),
+            Label::Yr10 => Tenor::Years(10),
+            Label::Yr20 => Tenor::Years(20),
+            Label::Yr30 => Tenor::Years(30),
+            Label::Mo1_5 => Tenor::Days(42),
+        }
+    }
+
+    /// every label in the order of the csv headers
+    pub fn all() -> &'static [Label] {
+        &LABELS
+    }
+
+    /// every label shortest first, the order of the columns in the Treasury download
+    pub fn by_tenor() -> Vec<Label> {
+        let mut labels = LABELS.to_vec();
+        labels.sort_by_key(|l| l.tenor());
+        labels
+    }
+}
+
+impl From<Label> for Tenor {
+    fn from(label: Label) -> Self {
+        label.tenor()
+    }
 }
 
 /// Captures one curve for a single date
-/// order of data matches 'CURVE_LABELS'
-#[derive(Clone, Copy, Debug, Default)]
-pub struct TreasuryCurve([Option<f64>; 13]);
+/// published points are held by [`Tenor`] shortest first, tenors not published for the date
+/// are left out. Columns that aren't a tenor are kept by name when parsing with
+/// [`ParseOptions::lenient`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TreasuryCurve {
+    points: Vec<(Tenor, f64)>,
+    extra: Vec<(String, Option<f64>)>,
+}
 
 impl TreasuryCurve {
+    /// curve from (tenor, yield) points in any order, a later point replaces one with the same tenor
+    pub fn new(points: impl IntoIterator<Item = (Tenor, f64)>) -> Self {
+        let mut curve = TreasuryCurve::default();
+        for (tenor, value) in points {
+            curve.insert(tenor, value);
+        }
+        curve
+    }
+
+    /// add or replace the point for `tenor`
+    pub fn insert(&mut self, tenor: Tenor, value: f64) {
+        match self.points.binary_search_by_key(&tenor, |(t, _)| *t) {
+            Ok(index) => self.points[index].1 = value,
+            Err(index) => self.points.insert(index, (tenor, value)),
+        }
+    }
+
+    pub fn get(&self, tenor: Tenor) -> Option<f64> {
+        self.points
+            .binary_search_by_key(&tenor, |(t, _)| *t)
+            .ok()
+            .map(|index| self.points[index].1)
+    }
+
     pub fn get_label(&self, label: Label) -> Option<f64> {
-        self.0[label.index()]
+        self.get(label.tenor())
+    }
+
+    /// published points, shortest tenor first
+    pub fn points(&self) -> &[(Tenor, f64)] {
+        &self.points
+    }
+
+    pub fn tenors(&self) -> impl Iterator<Item = Tenor> + '_ {
+        self.points.iter().map(|(t, _)| *t)
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// columns that aren't a tenor, ie. a new statistic added after this release
+    pub fn extra(&self) -> &[(String, Option<f64>)] {
+        &self.extra
+    }
+
+    /// value of a column that isn't a tenor, by its csv header
+    pub fn get_extra(&self, name: &str) -> Option<f64> {
+        self.extra
+            .iter()
+            .find(|(n, _)| n == name)
+            .and_then(|(_, v)| *v)
+    }
+}
+
+impl TreasuryDataset for TreasuryCurve {
+    const DATA_TYPE: &'static str = YIELD_CURVE;
+    const HEADERS: &'static [&'static str] = &CURVE_HEADERS;
+    const XML_FIELDS: &'static [&'static str] = &XML_FIELDS;
+
+    fn from_values(values: &[Option<f64>]) -> Self {
+        TreasuryCurve::new(
+            LABELS
+                .iter()
+                .zip(values)
+                .filter_map(|(label, value)| Some((label.tenor(), (*value)?))),
+        )
+    }
+
+    fn values(&self) -> Vec<Option<f64>> {
+        LABELS.iter().map(|label| self.get_label(*label)).collect()
+    }
+
+    /// columns named like a tenor, ie. `9 Mo`, are added to the curve. A column for the tenor
+    /// of a label or one already on the curve is kept in `extra` so it never replaces a point
+    fn with_extra(mut self, extra: Vec<(String, Option<f64>)>) -> Self {
+        for (name, value) in extra {
+            let tenor = Tenor::parse(&name).filter(|tenor| {
+                self.get(*tenor).is_none() && LABELS.iter().all(|l| l.tenor() != *tenor)
+            });
+            match tenor {
+                Some(tenor) => {
+                    if let Some(value) = value {
+                        self.insert(tenor, value);
+                    }
+                }
+                None => self.extra.push((name, value)),
+            }
+        }
+        self
     }
 }
 
 /// stores the treasury curve in csv format as fetched from US Treasury website
 pub struct TreasuryCurveCsv(pub String);
 
+/// stores the treasury curve in the OData xml format as fetched from US Treasury website
+pub struct TreasuryCurveXml(pub String);
+
 /// Hold Treasury Curve history
-#[derive(Debug)]
 /// curve history stored in reverse with latest at top
-pub struct TreasuryCurveHistory {
-    curves: Vec<TreasuryCurve>,
-    dates: Vec<Date>,
+pub type TreasuryCurveHistory = DatasetHistory<TreasuryCurve>;
+
+/// Rows of a csv written by [`TreasuryCurveHistory::write_csv`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Layout {
+    /// one row per date with a column per tenor, the layout published by the Treasury
+    #[default]
+    Wide,
+    /// one `Date,Tenor,Yield` row per published point
+    Long,
+}
+
+/// Controls how [`TreasuryCurveHistory::write_csv`] lays out the csv, the default matches the
+/// Treasury download
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WriteOptions {
+    pub layout: Layout,
+    /// `2023-07-07` instead of the Treasury's `07/07/2023`
+    pub iso_dates: bool,
+    /// earliest date at the top instead of the latest
+    pub oldest_first: bool,
+    /// only write these tenors, `None` writes every tenor published on any date along with the
+    /// columns kept by [`ParseOptions::lenient`]
+    pub tenors: Option<Vec<Tenor>>,
 }
 
-impl TryFrom<TreasuryCurveCsv> for TreasuryCurveHistory {
-    type Error = TreasuryCurveError;
-
-    /// convert csv file with header
-    fn try_from(value: TreasuryCurveCsv) -> Result<Self, Self::Error> {
-        // check spacing relative to `CurveLocation`
-        // if there is missing members than indicate None
-        // any errors return an empty array
-        let lines: Vec<&str> = value.0.split('\n').collect();
-        // set flags based on headers - flag is 16 bits but only using first 13 to line up with labels
-        let headers = lines[0].replace('\"', "");
-        let headers: Vec<&str> = headers.split(',').collect();
-        let flags = active_flags(&headers)?;
-        // load data into vector of `TreasuryCuve`
-        let curves: Vec<TreasuryCurve> = lines
+impl TreasuryCurveHistory {
+    /// write the history as csv, the default [`WriteOptions`] give a csv that parses back to
+    /// the same history with [`TryFrom<TreasuryCurveCsv>`]
+    pub fn write_csv(
+        &self,
+        mut writer: impl Write,
+        options: &WriteOptions,
+    ) -> Result<(), TreasuryCurveError> {
+        let fd = if options.iso_dates {
+            utility::date_format_iso()
+        } else {
+            utility::date_format_header()
+        };
+        let mut tenors = match &options.tenors {
+            Some(tenors) => tenors.clone(),
+            None => self.records.iter().flat_map(|c| c.tenors()).collect(),
+        };
+        tenors.sort();
+        tenors.dedup();
+        // extras are only written with every tenor as a subset asks for tenors alone
+        let mut extras: Vec<&str> = vec![];
+        if options.tenors.is_none() && options.layout == Layout::Wide {
+            for (name, _) in self.records.iter().flat_map(|c| c.extra()) {
+                if !extras.contains(&name.as_str()) {
+                    extras.push(name);
+                }
+            }
+        }
+
+        let mut rows = self.dates.iter().zip(&self.records).collect::<Vec<_>>();
+        if options.oldest_first {
+            rows.reverse();
+        }
+
+        let headers = match options.layout {
+            Layout::Wide => tenors
+                .iter()
+                .map(|t| tenor_header(*t))
+                .chain(extras.iter().map(|e| e.to_string()))
+                .collect(),
+            Layout::Long => vec!["Tenor".to_string(), "Yield".to_string()],
+        };
+        // header quoted like the Treasury download, ie. `Date,"1 Mo","2 Mo"`
+        let quoted = headers
             .iter()
-            .skip(1)
-            .map(|l| load_curve(l, &flags))
-            .collect();
-        let dates: Vec<Date> = lines.iter().skip(1).map(|l| load_date(l)).collect();
-        let (dates, curves) = sort_arrays(dates, curves, false);
+            .map(|h| format!(",\"{}\"", h.replace('"', "\"\"")))
+            .collect::<String>();
+        writeln!(writer, "Date{quoted}").map_err(write_error)?;
+        let mut csv = WriterBuilder::new().flexible(true).from_writer(writer);
+        for (date, curve) in rows {
+            let date = date.format(&fd).map_err(write_error)?;
+            match options.layout {
+                Layout::Wide => {
+                    let values = tenors
+                        .iter()
+                        .map(|t| curve.get(*t))
+                        .chain(extras.iter().map(|e| curve.get_extra(e)))
+                        .map(|v| v.map(|v| v.to_string()).unwrap_or_default());
+                    csv.write_record(std::iter::once(date).chain(values))
+                        .map_err(write_error)?;
+                }
+                Layout::Long => {
+                    for (tenor, value) in curve.points() {
+                        if tenors.contains(tenor) {
+                            csv.write_record([
+                                date.clone(),
+                                tenor_header(*tenor),
+                                value.to_string(),
+                            ])
+                            .map_err(write_error)?;
+                        }
+                    }
+                }
+            }
+        }
+        csv.flush().map_err(write_error)
+    }
+}
 
-        Ok(TreasuryCurveHistory { curves, dates })
+// csv header of a tenor, the Treasury's header when it is a label, ie. `1.5 Month`
+pub(crate) fn tenor_header(tenor: Tenor) -> String {
+    match LABELS.iter().find(|l| l.tenor() == tenor) {
+        Some(label) => CURVE_HEADERS[label.index()].to_string(),
+        None => tenor.to_string(),
     }
 }
 
-impl TreasuryCurveHistory {
-    /// grab the latest date in curve history
-    pub fn latest(&self) -> (Date, TreasuryCurve) {
-        (self.dates[0], self.curves[0])
+fn write_error(e: impl std::fmt::Display) -> TreasuryCurveError {
+    TreasuryCurveError::WriteCsv(e.to_string())
+}
+
+/// index of `request_date` or the closest date before it in `dates` (latest first),
+/// allowing up to 5 days after the last date
+pub(crate) fn date_index(dates: &[Date], request_date: Date) -> Result<usize, TreasuryCurveError> {
+    // check that date request matches the year range of the data
+    if dates.is_empty()
+        || request_date < *dates.last().unwrap()
+        || request_date > (*dates.first().unwrap() + MAX_FORWARD_DAYS.days())
+    {
+        Err(TreasuryCurveError::OutsideDateRange(
+            request_date.to_string(),
+        ))
+    } else {
+        Ok(closest_date(dates, request_date))
     }
+}
 
-    /// grab the date specified or a date prior if curve does not exist for specified date
-    /// allow 5 days after last published curve
-    pub fn from_date(
-        &self,
-        request_date: Date,
-    ) -> Result<(Date, TreasuryCurve), TreasuryCurveError> {
-        // check that date request matches the year range of the data
-        if request_date < *self.dates.last().unwrap()
-            || request_date > (*self.dates.first().unwrap() + MAX_FORWARD_DAYS.days())
-        {
-            Err(TreasuryCurveError::OutsideDateRange(
-                request_date.to_string(),
-            ))
-        } else {
-            let index = self.closest_date(request_date);
-            Ok((self.dates[index], self.curves[index]))
+// grab exact date or closest working backwards in time
+fn closest_date(dates: &[Date], request_date: Date) -> usize {
+    if request_date >= *dates.first().unwrap() {
+        0
+    } else if request_date <= *dates.last().unwrap() {
+        dates.len() - 1
+    } else {
+        let mut index = 0;
+        let mut found = false;
+        while !found {
+            index += 1;
+            if dates[index] <= request_date {
+                found = true;
+            }
         }
+        index
     }
+}
 
-    // grab exact date or closest working backwards in time
-    fn closest_date(&self, request_date: Date) -> usize {
-        if request_date >= *self.dates.first().unwrap() {
-            0
-        } else if request_date <= *self.dates.last().unwrap() {
-            self.dates.len() - 1
-        } else {
-            let mut index = 0;
-            let mut found = false;
-            while !found {
-                index += 1;
-                if self.dates[index] <= request_date {
-                    found = true;
+/// dates, records and the rows skipped with [`ParseOptions::skip_invalid_rows`]
+pub(crate) type ParsedCsv<D> = (Vec<Date>, Vec<D>, Vec<TreasuryCurveError>);
+
+/// parse csv with a header into dates and records, latest first
+pub(crate) fn parse_csv<D: TreasuryDataset>(
+    csv: impl Read,
+    options: ParseOptions,
+) -> Result<ParsedCsv<D>, TreasuryCurveError> {
+    let mut rows = CsvRecords::<_, D>::new(csv, options)?;
+    let (dates, records) = rows
+        .by_ref()
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .unzip();
+    let (dates, records) = sort_arrays(dates, records, false);
+    Ok((dates, records, rows.warnings))
+}
+
+/// Records of a csv read row by row from any reader, ie. a file or stdin, in the order of the csv
+///
+/// fields may be quoted, lines end in LF or CRLF and blank lines and a leading BOM are skipped.
+/// Iteration stops after the first error, rows left out with
+/// [`ParseOptions::skip_invalid_rows`] are kept in [`CsvRecords::warnings`]
+pub struct CsvRecords<R, D> {
+    rows: StringRecordsIntoIter<SkipBom<R>>,
+    headers: StringRecord,
+    columns: Vec<Option<usize>>,
+    options: ParseOptions,
+    warnings: Vec<TreasuryCurveError>,
+    done: bool,
+    dataset: PhantomData<D>,
+}
+
+impl<R: Read, D: TreasuryDataset> CsvRecords<R, D> {
+    /// read the header and check its labels, rows are read as the iterator is used
+    pub fn new(reader: R, options: ParseOptions) -> Result<Self, TreasuryCurveError> {
+        let reader = SkipBom::new(reader).map_err(|e| row_error(1, "", &e.to_string()))?;
+        let mut rows = ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .trim(Trim::All)
+            .from_reader(reader)
+            .into_records();
+        let headers = match rows.next() {
+            Some(headers) => headers.map_err(csv_error)?,
+            None => StringRecord::new(),
+        };
+        if headers.get(0) != Some("Date") {
+            return Err(row_error(
+                1,
+                &raw_row(&headers),
+                "header does not start with Date",
+            ));
+        }
+        // position in `D::HEADERS` of each column after the date
+        let columns = label_columns(&headers, D::HEADERS);
+        if !options.lenient {
+            if let Some((h, _)) = headers
+                .iter()
+                .skip(1)
+                .zip(&columns)
+                .find(|(_, c)| c.is_none())
+            {
+                return Err(TreasuryCurveError::MissingLabel(h.to_string()));
+            }
+        }
+        Ok(CsvRecords {
+            rows,
+            headers,
+            columns,
+            options,
+            warnings: vec![],
+            done: false,
+            dataset: PhantomData,
+        })
+    }
+
+    /// rows left out so far with [`ParseOptions::skip_invalid_rows`]
+    pub fn warnings(&self) -> &[TreasuryCurveError] {
+        &self.warnings
+    }
+
+    fn load_row(&self, fields: &StringRecord) -> Result<(Date, D), String> {
+        if fields.len() != self.headers.len() {
+            return Err(format!(
+                "expected {} fields, found {}",
+                self.headers.len(),
+                fields.len()
+            ));
+        }
+        let date = load_date(fields)?;
+        let record = D::from_values(&load_values(fields, &self.columns, D::HEADERS.len())?);
+        if !self.columns.iter().any(Option::is_none) {
+            return Ok((date, record));
+        }
+        // when lenient the columns that aren't labels are kept by name
+        let extra = self
+            .headers
+            .iter()
+            .zip(fields)
+            .skip(1)
+            .zip(&self.columns)
+            .filter(|(_, c)| c.is_none())
+            .map(|((h, d), _)| (h.to_string(), d.parse::<f64>().ok()))
+            .collect();
+        Ok((date, record.with_extra(extra)))
+    }
+}
+
+impl<R: Read, D: TreasuryDataset> Iterator for CsvRecords<R, D> {
+    type Item = Result<(Date, D), TreasuryCurveError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let fields = match self.rows.next()? {
+                Ok(fields) => fields,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(csv_error(e)));
+                }
+            };
+            let line = fields.position().map_or(0, |p| p.line() as usize);
+            match self.load_row(&fields) {
+                Ok(row) => return Some(Ok(row)),
+                Err(reason) if self.options.skip_invalid_rows => {
+                    self.warnings
+                        .push(row_error(line, &raw_row(&fields), &reason));
+                }
+                Err(reason) => {
+                    self.done = true;
+                    return Some(Err(row_error(line, &raw_row(&fields), &reason)));
                 }
             }
-            index
         }
+        None
     }
 }
 
-// determine of 13 labels which ones are active and exist
-fn active_flags(headers: &[&str]) -> Result<u16, TreasuryCurveError> {
-    let mut flags = 0;
-    // ignore first label as "DATE"
-    for h in headers.iter().skip(1) {
-        match search_labels(h) {
-            Some(index) => flags |= 1 << index,
-            None => return Err(TreasuryCurveError::MissingLabel(h.to_string())),
+// reader without the utf-8 byte order mark some exports start with, or the unmatched quote
+// before `Date` older downloads have
+struct SkipBom<R> {
+    start: Vec<u8>,
+    reader: R,
+}
+
+impl<R: Read> SkipBom<R> {
+    fn new(mut reader: R) -> std::io::Result<Self> {
+        let bom = "\u{feff}".as_bytes();
+        let stray_quote = b"\"Date,";
+        let mut start = vec![];
+        // readers may hand back a byte at a time
+        while start.len() < bom.len() + stray_quote.len() {
+            let mut byte = [0; 1];
+            match reader.read(&mut byte) {
+                Ok(0) => break,
+                Ok(_) => start.push(byte[0]),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
         }
+        if start.starts_with(bom) {
+            start.drain(..bom.len());
+        }
+        if start.starts_with(stray_quote) {
+            start.remove(0);
+        }
+        Ok(SkipBom { start, reader })
     }
-    Ok(flags)
 }
 
-fn search_labels(label: &str) -> Option<usize> {
-    CURVE_HEADERS.iter().position(|l| (*l).eq(label))
+impl<R: Read> Read for SkipBom<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.start.is_empty() {
+            return self.reader.read(buf);
+        }
+        let n = self.start.len().min(buf.len());
+        buf[..n].copy_from_slice(&self.start[..n]);
+        self.start.drain(..n);
+        Ok(n)
+    }
 }
 
-// load raw data into curve depending on which bits are active in flags
-fn load_curve(data: &str, flags: &u16) -> TreasuryCurve {
-    let mut data: Vec<Option<f64>> = data
-        .split(',')
-        .skip(1)
-        .map(|d| d.parse::<f64>().ok())
-        .collect();
-    if u16::count_ones(*flags) != 13 {
-        // search for zero bits in flag and shift data vector over
-        for i in 0..CURVE_LENGTH {
-            if (flags >> i) & 1 == 0 {
-                data.insert(i, None);
+// row as it would be written back without quotes, used in errors
+fn raw_row(fields: &StringRecord) -> String {
+    fields.iter().collect::<Vec<_>>().join(",")
+}
+
+fn csv_error(e: csv::Error) -> TreasuryCurveError {
+    let line = e.position().map_or(0, |p| p.line() as usize);
+    row_error(line, "", &e.to_string())
+}
+
+fn row_error(line: usize, row: &str, reason: &str) -> TreasuryCurveError {
+    TreasuryCurveError::ParseCsv {
+        line,
+        row: row.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+/// parse the xml feed into dates and records, latest first
+pub(crate) fn parse_xml<D: TreasuryDataset>(
+    xml: &str,
+) -> Result<(Vec<Date>, Vec<D>), TreasuryCurveError> {
+    let mut dates = vec![];
+    let mut records = vec![];
+    for entry in xml::properties(xml)? {
+        let mut date = None;
+        let mut values = vec![None; D::XML_FIELDS.len()];
+        for (name, data) in entry {
+            if name == XML_DATE_FIELD {
+                date = data.as_deref().map(load_xml_date).transpose()?;
+            } else if let Some(index) = D::XML_FIELDS.iter().position(|f| *f == name) {
+                values[index] = data.and_then(|d| d.parse::<f64>().ok());
             }
         }
+        dates.push(date.ok_or_else(|| {
+            TreasuryCurveError::WebParseXml(format!("entry without {XML_DATE_FIELD}"))
+        })?);
+        records.push(D::from_values(&values));
+    }
+    Ok(sort_arrays(dates, records, false))
+}
+
+// position of each column in `labels` ignoring the first column as "DATE",
+// `None` for columns that aren't labels
+fn label_columns(headers: &StringRecord, labels: &[&str]) -> Vec<Option<usize>> {
+    headers
+        .iter()
+        .skip(1)
+        .map(|h| search_labels(h, labels))
+        .collect()
+}
+
+fn search_labels(label: &str, labels: &[&str]) -> Option<usize> {
+    labels.iter().position(|l| (*l).eq(label))
+}
+
+// load raw data into one value per label using the position of each column,
+// labels without a column and values that are empty or `N/A` are `None`
+fn load_values(
+    fields: &StringRecord,
+    columns: &[Option<usize>],
+    length: usize,
+) -> Result<Vec<Option<f64>>, String> {
+    let mut values = vec![None; length];
+    for (d, column) in fields.iter().skip(1).zip(columns) {
+        if let Some(index) = column {
+            values[*index] = match d {
+                "" | "N/A" => None,
+                _ => Some(
+                    d.parse::<f64>()
+                        .map_err(|_| format!("`{d}` is not a number"))?,
+                ),
+            };
+        }
     }
-    // TODO: Remove Panic and introduce result return
-    TreasuryCurve(
-        data.as_slice()
-            .try_into()
-            .expect("data conversion for row doesn't equal CURVE_LENGTH"),
-    )
+    Ok(values)
 }
 
-fn load_date(data: &str) -> Date {
+fn load_date(fields: &StringRecord) -> Result<Date, String> {
     let fd = utility::date_format_header();
-    //let fd = format_description::parse("[month]/[day]/[year]").unwrap();
-    let string_date = data.split(',').next().unwrap();
-    Date::parse(string_date, &fd).unwrap()
+    let string_date = fields.get(0).unwrap_or_default();
+    Date::parse(string_date, &fd).map_err(|_| format!("`{string_date}` is not a date"))
+}
+
+// xml dates are `2023-01-03T00:00:00`
+fn load_xml_date(data: &str) -> Result<Date, TreasuryCurveError> {
+    let fd = utility::date_format_iso();
+    data.get(..10)
+        .and_then(|d| Date::parse(d, &fd).ok())
+        .ok_or_else(|| TreasuryCurveError::WebParseXml(format!("invalid date {data}")))
 }
 
 fn sort_arrays<C, D>(primary: Vec<D>, secondary: Vec<C>, ascending: bool) -> (Vec<D>, Vec<C>)
@@ -177,7 +677,7 @@ where
     D: Ord,
 {
     // zip vectors, sort, unzip
-    let mut zipped: Vec<_> = primary.into_iter().zip(secondary.into_iter()).collect();
+    let mut zipped: Vec<_> = primary.into_iter().zip(secondary).collect();
     if ascending {
         zipped.sort_by(|a, b| a.0.cmp(&b.0));
     } else {
@@ -192,6 +692,10 @@ where
 mod tests {
     use super::*;
 
+    fn row(data: &str) -> StringRecord {
+        StringRecord::from(data.split(',').collect::<Vec<_>>())
+    }
+
     fn new_csv_data() -> &'static str {
         r###""Date,"1 Mo","2 Mo","3 Mo","4 Mo","6 Mo","1 Yr","2 Yr","3 Yr","5 Yr","7 Yr","10 Yr","20 Yr","30 Yr"
 07/07/2023,5.32,5.47,5.46,5.52,5.53,5.41,4.94,4.64,4.35,4.23,4.06,4.27,4.05
@@ -211,8 +715,8 @@ mod tests {
             "Date", "1 Mo", "2 Mo", "3 Mo", "4 Mo", "6 Mo", "1 Yr", "2 Yr", "3 Yr", "5 Yr", "7 Yr",
             "10 Yr", "20 Yr", "30 Yr",
         ];
-        let flags = active_flags(&headers).unwrap();
-        assert_eq!(flags, 0b1111111111111);
+        let columns = label_columns(&StringRecord::from(headers), &CURVE_HEADERS);
+        assert_eq!(columns, (0..13).map(Some).collect::<Vec<_>>());
     }
 
     #[test]
@@ -221,8 +725,11 @@ mod tests {
             "Date", "1 Mo", "2 Mo", "3 Mo", "6 Mo", "1 Yr", "2 Yr", "5 Yr", "7 Yr", "10 Yr",
             "20 Yr", "30 Yr",
         ];
-        let flags = active_flags(&headers).unwrap();
-        assert_eq!(flags, 0b1111101110111);
+        let columns = label_columns(&StringRecord::from(headers), &CURVE_HEADERS);
+        assert_eq!(
+            columns,
+            [0, 1, 2, 4, 5, 6, 8, 9, 10, 11, 12].map(Some).to_vec()
+        );
     }
 
     #[test]
@@ -232,50 +739,336 @@ mod tests {
             "Date", "1 Mo", "2 Mo", "3 Mo", "4 Mo", "9 Mo", "1 Yr", "2 Yr", "3 Yr", "5 Yr", "7 Yr",
             "10 Yr", "20 Yr", "30 Yr",
         ];
-        let flags = active_flags(&headers);
-        assert!(flags.is_err());
+        let columns = label_columns(&StringRecord::from(headers), &CURVE_HEADERS);
+        assert_eq!(columns[4], None);
+    }
+
+    #[test]
+    fn parse_1_5_month_column() {
+        // published from 2025 between the 1 and 2 month
+        let csv = r###"Date,"1 Mo","1.5 Month","2 Mo","3 Mo","4 Mo","6 Mo","1 Yr","2 Yr","3 Yr","5 Yr","7 Yr","10 Yr","20 Yr","30 Yr"
+02/19/2025,4.32,4.32,4.33,4.34,4.32,4.29,4.24,4.26,4.29,4.37,4.46,4.55,4.81,4.77
+02/18/2025,4.31,4.31,4.33,4.33,4.31,4.28,4.22,4.28,4.31,4.37,4.46,4.55,4.82,4.77"###;
+        let tc = TreasuryCurveHistory::try_from(TreasuryCurveCsv(csv.to_string())).unwrap();
+        let (_, curve) = tc.latest();
+        assert_eq!(curve.get_label(Label::Mo1), Some(4.32));
+        assert_eq!(curve.get_label(Label::Mo1_5), Some(4.32));
+        assert_eq!(curve.get_label(Label::Mo2), Some(4.33));
+        assert_eq!(curve.get_label(Label::Yr30), Some(4.77));
+        assert!(curve.extra().is_empty());
+    }
+
+    #[test]
+    fn lenient_keeps_unknown_columns() {
+        let csv = "Date,\"1 Mo\",\"9 Mo\",\"1 Yr\",\"Note\"\n07/07/2023,5.32,5.45,5.41,1\n07/06/2023,5.32,,5.44,";
+        assert_eq!(
+            TreasuryCurveHistory::try_from(TreasuryCurveCsv(csv.to_string())),
+            Err(TreasuryCurveError::MissingLabel("9 Mo".to_string()))
+        );
+        let tc = TreasuryCurveHistory::from_csv(
+            csv,
+            ParseOptions {
+                lenient: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let (_, curve) = tc.latest();
+        assert_eq!(curve.get_label(Label::Mo1), Some(5.32));
+        assert_eq!(curve.get_label(Label::Yr1), Some(5.41));
+        // columns named like a tenor join the curve, anything else is kept by name
+        assert_eq!(curve.get(Tenor::Months(9)), Some(5.45));
+        assert_eq!(curve.get_extra("Note"), Some(1.0));
+        assert_eq!(tc.records[1].get(Tenor::Months(9)), None);
+        assert_eq!(tc.records[1].extra(), &[("Note".to_string(), None)]);
+    }
+
+    #[test]
+    fn extra_columns_never_replace_labels() {
+        let csv = "Date,\"1 Mo\",\"10 Yr\",\"10 Yr Breakeven\",\"10 Year\"\n07/07/2023,5.32,4.06,2.25,9.99";
+        let options = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let (_, curve) = TreasuryCurveHistory::from_csv(csv, options)
+            .unwrap()
+            .latest();
+        assert_eq!(curve.get_label(Label::Yr10), Some(4.06));
+        assert_eq!(curve.get_extra("10 Yr Breakeven"), Some(2.25));
+        // a second spelling of a label is kept apart from the published point
+        assert_eq!(curve.get_extra("10 Year"), Some(9.99));
+        assert_eq!(curve.len(), 2);
+    }
+
+    #[test]
+    fn bad_rows_report_line_and_reason() {
+        let truncated = "Date,\"1 Mo\",\"2 Mo\"\n07/07/2023,5.32,5.47\n07/06/2023,5.3";
+        assert_eq!(
+            TreasuryCurveHistory::try_from(TreasuryCurveCsv(truncated.to_string())),
+            Err(TreasuryCurveError::ParseCsv {
+                line: 3,
+                row: "07/06/2023,5.3".to_string(),
+                reason: "expected 3 fields, found 2".to_string()
+            })
+        );
+        let bad_date = "Date,\"1 Mo\"\n2023-07-07,5.32";
+        assert!(matches!(
+            TreasuryCurveHistory::try_from(TreasuryCurveCsv(bad_date.to_string())),
+            Err(TreasuryCurveError::ParseCsv { line: 2, reason, .. }) if reason == "`2023-07-07` is not a date"
+        ));
+        let bad_value = "Date,\"1 Mo\"\n07/07/2023,5.3x";
+        assert!(matches!(
+            TreasuryCurveHistory::try_from(TreasuryCurveCsv(bad_value.to_string())),
+            Err(TreasuryCurveError::ParseCsv { reason, .. }) if reason == "`5.3x` is not a number"
+        ));
+        let html = "<!DOCTYPE html>\n<html><body>Service Unavailable</body></html>";
+        assert!(matches!(
+            TreasuryCurveHistory::try_from(TreasuryCurveCsv(html.to_string())),
+            Err(TreasuryCurveError::ParseCsv { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn skip_invalid_rows_keeps_warnings() {
+        let csv = "Date,\"1 Mo\",\"2 Mo\"\n07/07/2023,5.32,5.47\n07/O6/2023,5.32,5.47\n07/05/2023,5.28,5.38\n07/03/2023,5.2";
+        let options = ParseOptions {
+            skip_invalid_rows: true,
+            ..Default::default()
+        };
+        let tc = TreasuryCurveHistory::from_csv(csv, options).unwrap();
+        assert_eq!(tc.len(), 2);
+        assert_eq!(
+            tc.dates[1],
+            Date::from_calendar_date(2023, time::Month::July, 5).unwrap()
+        );
+        let lines: Vec<usize> = tc
+            .warnings()
+            .iter()
+            .map(|w| match w {
+                TreasuryCurveError::ParseCsv { line, .. } => *line,
+                _ => 0,
+            })
+            .collect();
+        assert_eq!(lines, vec![3, 5]);
+    }
+
+    #[test]
+    fn bom_crlf_and_blank_lines_match_plain_csv() {
+        let plain = TreasuryCurveHistory::try_from(TreasuryCurveCsv(
+            include_str!("../tests/fixtures/yield_curve_2023.csv").to_string(),
+        ))
+        .unwrap();
+        let crlf = TreasuryCurveHistory::try_from(TreasuryCurveCsv(
+            include_str!("../tests/fixtures/yield_curve_2023_bom_crlf.csv").to_string(),
+        ))
+        .unwrap();
+        assert_eq!(crlf, plain);
+        // 30 year is the last column so it would hold the `\r`
+        assert_eq!(crlf.latest().1.get_label(Label::Yr30), Some(4.05));
+    }
+
+    // hands out one byte per read like a slow network stream
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.0.split_first() {
+                Some((byte, rest)) if !buf.is_empty() => {
+                    buf[0] = *byte;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn records_stream_from_a_reader() {
+        let csv = include_bytes!("../tests/fixtures/yield_curve_2023_bom_crlf.csv");
+        let rows: Vec<(Date, TreasuryCurve)> =
+            CsvRecords::new(Trickle(csv), ParseOptions::default())
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+        assert_eq!(rows.len(), 10);
+        assert_eq!(
+            rows[0].0,
+            Date::from_calendar_date(2023, time::Month::July, 7).unwrap()
+        );
+        assert_eq!(rows[0].1.get_label(Label::Mo1), Some(5.32));
+
+        let history =
+            TreasuryCurveHistory::from_reader(Trickle(csv), ParseOptions::default()).unwrap();
+        assert_eq!(
+            history,
+            TreasuryCurveHistory::from_csv(
+                include_str!("../tests/fixtures/yield_curve_2023.csv"),
+                ParseOptions::default()
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn records_stop_at_the_first_error() {
+        let csv = "Date,\"1 Mo\"\n07/07/2023,5.32\n07/06/2023,bad\n07/05/2023,5.28";
+        let mut rows =
+            CsvRecords::<_, TreasuryCurve>::new(csv.as_bytes(), Default::default()).unwrap();
+        assert!(rows.next().unwrap().is_ok());
+        assert!(matches!(
+            rows.next(),
+            Some(Err(TreasuryCurveError::ParseCsv { line: 3, .. }))
+        ));
+        assert!(rows.next().is_none());
+
+        let options = ParseOptions {
+            skip_invalid_rows: true,
+            ..Default::default()
+        };
+        let mut rows = CsvRecords::<_, TreasuryCurve>::new(csv.as_bytes(), options).unwrap();
+        assert_eq!(rows.by_ref().count(), 2);
+        assert_eq!(rows.warnings().len(), 1);
+    }
+
+    #[test]
+    fn quoted_fields() {
+        let csv = "\"Date\",\"1 Mo\",\"Source, note\",\"30 Yr\"\n\"07/07/2023\",\"5.32\",\"auction, reopened\",\"\"\n";
+        let options = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let tc = TreasuryCurveHistory::from_csv(csv, options).unwrap();
+        assert_eq!(tc.len(), 1);
+        let (_, curve) = tc.latest();
+        assert_eq!(curve.get_label(Label::Mo1), Some(5.32));
+        assert_eq!(curve.get_label(Label::Yr30), None);
+        assert_eq!(curve.extra(), &[("Source, note".to_string(), None)]);
+    }
+
+    #[test]
+    fn write_csv_round_trips() {
+        // written back in the Treasury's layout, 1.5 Month between the 1 and 2 month
+        let csv = r###"Date,"1 Mo","1.5 Month","2 Mo","3 Mo","4 Mo","6 Mo","1 Yr","2 Yr","3 Yr","5 Yr","7 Yr","10 Yr","20 Yr","30 Yr"
+02/19/2025,4.32,4.32,4.33,4.34,4.32,4.29,4.24,4.26,4.29,4.37,4.46,4.55,4.81,4.77
+02/18/2025,4.31,,4.33,4.33,4.31,4.28,4.22,4.28,4.31,4.37,4.46,4.55,4.82,4.77
+"###;
+        let tc = TreasuryCurveHistory::try_from(TreasuryCurveCsv(csv.to_string())).unwrap();
+        let mut written = vec![];
+        tc.write_csv(&mut written, &WriteOptions::default())
+            .unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), csv);
+
+        for fixture in [
+            include_str!("../tests/fixtures/yield_curve_1999.csv"),
+            include_str!("../tests/fixtures/yield_curve_2020.csv"),
+            include_str!("../tests/fixtures/yield_curve_2023.csv"),
+        ] {
+            let tc = TreasuryCurveHistory::try_from(TreasuryCurveCsv(fixture.to_string())).unwrap();
+            let mut written = vec![];
+            tc.write_csv(&mut written, &WriteOptions::default())
+                .unwrap();
+            let written = String::from_utf8(written).unwrap();
+            assert_eq!(written.lines().next(), fixture.lines().next());
+            assert_eq!(
+                TreasuryCurveHistory::try_from(TreasuryCurveCsv(written)).unwrap(),
+                tc
+            );
+        }
+    }
+
+    #[test]
+    fn write_csv_options() {
+        let csv = "Date,\"1 Mo\",\"9 Mo\",\"1 Yr\",\"Note\"\n07/07/2023,5.32,5.45,5.41,1\n07/06/2023,5.32,,5.44,";
+        let options = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let tc = TreasuryCurveHistory::from_csv(csv, options).unwrap();
+        let write = |options: WriteOptions| {
+            let mut written = vec![];
+            tc.write_csv(&mut written, &options).unwrap();
+            String::from_utf8(written).unwrap()
+        };
+
+        // tenors that aren't labels and the extra columns are kept
+        let wide = write(WriteOptions::default());
+        assert_eq!(
+            wide,
+            "Date,\"1 Mo\",\"9 Mo\",\"1 Yr\",\"Note\"\n07/07/2023,5.32,5.45,5.41,1\n07/06/2023,5.32,,5.44,\n"
+        );
+        assert_eq!(TreasuryCurveHistory::from_csv(&wide, options).unwrap(), tc);
+
+        let subset = write(WriteOptions {
+            iso_dates: true,
+            oldest_first: true,
+            tenors: Some(vec![Tenor::Years(1), Tenor::Months(1)]),
+            ..Default::default()
+        });
+        assert_eq!(
+            subset,
+            "Date,\"1 Mo\",\"1 Yr\"\n2023-07-06,5.32,5.44\n2023-07-07,5.32,5.41\n"
+        );
+
+        // unpublished points are left out rather than written empty
+        let long = write(WriteOptions {
+            layout: Layout::Long,
+            iso_dates: true,
+            tenors: Some(vec![Tenor::Months(9), Tenor::Years(1)]),
+            ..Default::default()
+        });
+        assert_eq!(
+            long,
+            "Date,\"Tenor\",\"Yield\"\n2023-07-07,9 Mo,5.45\n2023-07-07,1 Yr,5.41\n2023-07-06,1 Yr,5.44\n"
+        );
     }
 
     #[test]
     fn check_parsing_curve_data_into_treasurycurve() {
         let data = "07/07/2023,5.32,5.47,5.46,5.52,5.53,5.41,4.94,4.64,4.35,4.23,4.06,4.27,4.05";
-        let flags: u16 = 0b1111111111111;
-        let curve = load_curve(data, &flags);
+        let columns: Vec<Option<usize>> = (0..13).map(Some).collect();
+        let curve =
+            TreasuryCurve::from_values(&load_values(&row(data), &columns, CURVE_LENGTH).unwrap());
         assert_eq!(curve.get_label(Label::Mo1), Some(5.32));
         assert_eq!(curve.get_label(Label::Yr30), Some(4.05));
+        assert_eq!(curve.len(), 13);
 
-        // data must be reduced to match number of flags or it will ***PANIC***
         let data = "07/07/2023,5.32,5.47,5.46,5.52,5.53,5.41,4.94,4.64,4.35,4.23";
-        let missingflags: u16 = 0b1111111010101;
-        let missingcurve = load_curve(data, &missingflags);
-        assert_eq!(missingcurve.0[1], None);
-        assert_eq!(missingcurve.0[3], None);
-        assert_eq!(missingcurve.0[5], None);
+        let missing: Vec<Option<usize>> = [0, 2, 4, 6, 7, 8, 9, 10, 11, 12].map(Some).to_vec();
+        let missingcurve =
+            TreasuryCurve::from_values(&load_values(&row(data), &missing, CURVE_LENGTH).unwrap());
+        assert_eq!(missingcurve.get_label(Label::Mo2), None);
+        assert_eq!(missingcurve.get_label(Label::Mo4), None);
+        assert_eq!(missingcurve.get_label(Label::Yr1), None);
+        assert_eq!(missingcurve.get_label(Label::Yr30), Some(4.23));
     }
 
     #[test]
     fn check_parsing_curve_data_with_missing_point_into_treasurycurve() {
         let data = "07/07/2023,5.32,,5.46,5.52,5.53,5.41,4.94,4.64,4.35,4.23,4.06,4.27,4.05";
-        let flags: u16 = 0b1111111111111;
-        let curve = load_curve(data, &flags);
+        let columns: Vec<Option<usize>> = (0..13).map(Some).collect();
+        let curve =
+            TreasuryCurve::from_values(&load_values(&row(data), &columns, CURVE_LENGTH).unwrap());
         assert_eq!(curve.get_label(Label::Mo1), Some(5.32));
         assert_eq!(curve.get_label(Label::Mo2), None);
         assert_eq!(curve.get_label(Label::Yr30), Some(4.05));
 
-        // data must be reduced to match number of flags or it will ***PANIC***
+        // short rows leave the remaining labels empty
         let data = "07/07/2023,5.32,5.47,5.46,5.52,5.53,5.41,4.94,4.64,4.35,";
-        let missingflags: u16 = 0b1111111010101;
-        let missingcurve = load_curve(data, &missingflags);
-        assert_eq!(missingcurve.0[1], None);
-        assert_eq!(missingcurve.0[3], None);
-        assert_eq!(missingcurve.0[5], None);
-        assert_eq!(missingcurve.0[12], None);
+        let missing: Vec<Option<usize>> = [0, 2, 4, 6, 7, 8, 9, 10, 11, 12].map(Some).to_vec();
+        let missingcurve =
+            TreasuryCurve::from_values(&load_values(&row(data), &missing, CURVE_LENGTH).unwrap());
+        assert_eq!(missingcurve.get_label(Label::Mo2), None);
+        assert_eq!(missingcurve.get_label(Label::Mo4), None);
+        assert_eq!(missingcurve.get_label(Label::Yr1), None);
+        assert_eq!(missingcurve.get_label(Label::Yr30), None);
+        assert_eq!(missingcurve.get_label(Label::Yr20), Some(4.35));
     }
 
     #[test]
     fn check_parsing_curve_data_into_date() {
         let data = "07/10/2023,5.32,5.47,5.46,5.52,5.53,5.41,4.94,4.64,4.35,4.23,4.06,4.27,4.05";
-        let date = load_date(data);
+        let date = load_date(&row(data)).unwrap();
         assert_eq!(
             date,
             Date::from_calendar_date(2023, time::Month::July, 10).unwrap()
@@ -302,7 +1095,7 @@ mod tests {
 12/11/2000,6.08,6.06,5.79,5.52,5.43,5.33,5.42,5.37,5.71,5.54
 12/08/2000,6.09,6.04,5.77,5.50,5.41,5.32,5.39,5.35,5.71,5.55"###;
         let tc = TreasuryCurveHistory::try_from(TreasuryCurveCsv(csvdata.to_string())).unwrap();
-        let first_curve = tc.curves[0];
+        let first_curve = tc.records[0].clone();
         assert_eq!(first_curve.get_label(Label::Mo1), None);
         assert_eq!(first_curve.get_label(Label::Mo2), None);
         assert_eq!(first_curve.get_label(Label::Mo3), Some(5.89));
@@ -314,11 +1107,12 @@ mod tests {
         // data from year 2023
         let csvdata = new_csv_data();
         let tc = TreasuryCurveHistory::try_from(TreasuryCurveCsv(csvdata.to_string())).unwrap();
-        let first_curve = tc.curves[0];
-        assert_eq!(first_curve.0[0], Some(5.32));
-        assert_eq!(first_curve.0[1], Some(5.47));
-        assert_eq!(first_curve.0[2], Some(5.46));
-        assert_eq!(first_curve.0[12], Some(4.05));
+        let first_curve = tc.records[0].clone();
+        assert_eq!(first_curve.get(Tenor::Months(1)), Some(5.32));
+        assert_eq!(first_curve.get(Tenor::Months(2)), Some(5.47));
+        assert_eq!(first_curve.get(Tenor::Months(3)), Some(5.46));
+        assert_eq!(first_curve.get(Tenor::Years(30)), Some(4.05));
+        assert_eq!(first_curve.points()[0], (Tenor::Months(1), 5.32));
     }
 
     #[test]
@@ -336,28 +1130,126 @@ mod tests {
         assert_eq!(secondary, vec![1, 2, 3, 4]);
     }
 
+    #[test]
+    fn merge_removes_duplicates_and_keeps_latest_first() {
+        let mut tc = TreasuryCurveHistory::try_from(TreasuryCurveCsv(
+            new_csv_data()
+                .lines()
+                .take(4)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ))
+        .unwrap();
+        let mut header_and_older: Vec<&str> = new_csv_data().lines().take(1).collect();
+        // overlaps on 07/05/2023 with a revised 1 Mo point
+        header_and_older
+            .push("07/05/2023,9.99,5.38,5.44,5.51,5.52,5.40,4.94,4.59,4.25,4.11,3.95,4.17,3.95");
+        header_and_older.extend(new_csv_data().lines().skip(4));
+        let older =
+            TreasuryCurveHistory::try_from(TreasuryCurveCsv(header_and_older.join("\n"))).unwrap();
+
+        tc.merge(older);
+        let all =
+            TreasuryCurveHistory::try_from(TreasuryCurveCsv(new_csv_data().to_string())).unwrap();
+        assert_eq!(tc.len(), all.len());
+        assert_eq!(tc.dates, all.dates);
+        assert_eq!(tc.records[2].get_label(Label::Mo1), Some(9.99));
+        assert_eq!(tc.records[3], all.records[3]);
+    }
+
+    #[test]
+    fn retain_range_trims_history() {
+        let mut tc =
+            TreasuryCurveHistory::try_from(TreasuryCurveCsv(new_csv_data().to_string())).unwrap();
+        tc.retain_range(
+            Date::from_calendar_date(2023, time::Month::June, 28).unwrap(),
+            Date::from_calendar_date(2023, time::Month::July, 4).unwrap(),
+        );
+        assert_eq!(tc.len(), 4);
+        assert_eq!(
+            tc.latest().0,
+            Date::from_calendar_date(2023, time::Month::July, 3).unwrap()
+        );
+
+        tc.retain_range(
+            Date::from_calendar_date(2023, time::Month::July, 4).unwrap(),
+            Date::from_calendar_date(2023, time::Month::July, 2).unwrap(),
+        );
+        assert!(tc.is_empty());
+    }
+
+    #[test]
+    fn xml_feed_matches_csv() {
+        let csv = TreasuryCurveHistory::try_from(TreasuryCurveCsv(
+            include_str!("../tests/fixtures/yield_curve_2023.csv").to_string(),
+        ))
+        .unwrap();
+        let xml = TreasuryCurveHistory::try_from(TreasuryCurveXml(
+            include_str!("../tests/fixtures/yield_curve_2023.xml").to_string(),
+        ))
+        .unwrap();
+        assert_eq!(xml, csv);
+    }
+
+    #[test]
+    fn xml_feed_with_null_and_missing_fields() {
+        let xml = xml::feed(
+            r#"<m:properties>
+            <d:NEW_DATE m:type="Edm.DateTime">2000-12-29T00:00:00</d:NEW_DATE>
+            <d:BC_1MONTH m:type="Edm.Double" m:null="true" />
+            <d:BC_3MONTH m:type="Edm.Double">5.89</d:BC_3MONTH>
+            <d:BC_30YEAR m:type="Edm.Double">5.46</d:BC_30YEAR>
+        </m:properties>"#,
+        );
+        let history = TreasuryCurveHistory::try_from(TreasuryCurveXml(xml)).unwrap();
+        let (date, curve) = history.latest();
+        assert_eq!(
+            date,
+            Date::from_calendar_date(2000, time::Month::December, 29).unwrap()
+        );
+        assert_eq!(curve.get_label(Label::Mo1), None);
+        assert_eq!(curve.get_label(Label::Mo2), None);
+        assert_eq!(curve.get_label(Label::Mo3), Some(5.89));
+        assert_eq!(curve.get_label(Label::Yr30), Some(5.46));
+
+        let no_date = xml::feed("<m:properties><d:BC_1MONTH>4.17</d:BC_1MONTH></m:properties>");
+        assert!(TreasuryCurveHistory::try_from(TreasuryCurveXml(no_date)).is_err());
+    }
+
     #[test]
     fn check_closest_date() {
         let csvdata = new_csv_data();
         let tc = TreasuryCurveHistory::try_from(TreasuryCurveCsv(csvdata.to_string())).unwrap();
         // date is below range
         assert_eq!(
-            tc.closest_date(Date::from_calendar_date(2023, time::Month::June, 25).unwrap()),
+            closest_date(
+                &tc.dates,
+                Date::from_calendar_date(2023, time::Month::June, 25).unwrap()
+            ),
             8
         );
         // date is exact
         assert_eq!(
-            tc.closest_date(Date::from_calendar_date(2023, time::Month::July, 3).unwrap()),
+            closest_date(
+                &tc.dates,
+                Date::from_calendar_date(2023, time::Month::July, 3).unwrap()
+            ),
             3
         );
         // date is above range
         assert_eq!(
-            tc.closest_date(Date::from_calendar_date(2023, time::Month::July, 2).unwrap()),
+            closest_date(
+                &tc.dates,
+                Date::from_calendar_date(2023, time::Month::July, 2).unwrap()
+            ),
             4
         );
         // date doesn't exist grab closest
         assert_eq!(
-            tc.closest_date(Date::from_calendar_date(2023, time::Month::July, 10).unwrap()),
+            closest_date(
+                &tc.dates,
+                Date::from_calendar_date(2023, time::Month::July, 10).unwrap()
+            ),
             0
         );
     }
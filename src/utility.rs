@@ -1,16 +1,34 @@
 use time::{
     format_description::{self, FormatItem},
-    OffsetDateTime,
+    Date, OffsetDateTime,
 };
 
 pub(crate) fn current_year() -> i32 {
     OffsetDateTime::now_utc().year()
 }
 
+pub(crate) fn today() -> Date {
+    OffsetDateTime::now_utc().date()
+}
+
 pub(crate) fn date_format_header() -> Vec<FormatItem<'static>> {
-    format_description::parse("[month]/[day]/[year]").unwrap()
+    format_description::parse_borrowed::<1>("[month]/[day]/[year]").unwrap()
+}
+
+pub(crate) fn date_format_iso() -> Vec<FormatItem<'static>> {
+    format_description::parse_borrowed::<1>("[year]-[month]-[day]").unwrap()
+}
+
+// julian day of 1970-01-01, Arrow and polars dates count days from it
+#[cfg(any(feature = "arrow", feature = "polars"))]
+const UNIX_EPOCH_JULIAN_DAY: i32 = 2_440_588;
+
+#[cfg(any(feature = "arrow", feature = "polars"))]
+pub(crate) fn days_since_epoch(date: Date) -> i32 {
+    date.to_julian_day() - UNIX_EPOCH_JULIAN_DAY
 }
 
-pub(crate) fn date_format_error() -> Vec<FormatItem<'static>> {
-    format_description::parse("[year]-[month]-[day]").unwrap()
+#[cfg(any(feature = "arrow", feature = "polars"))]
+pub(crate) fn date_from_epoch_days(days: i32) -> Option<Date> {
+    Date::from_julian_day(days.checked_add(UNIX_EPOCH_JULIAN_DAY)?).ok()
 }
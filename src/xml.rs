@@ -0,0 +1,181 @@
+//! Reader for the Treasury OData (Atom) xml feed
+//!
+//! the feed holds one `<entry>` per date with the values inside `<m:properties>`, ie.
+//! `<d:BC_1MONTH m:type="Edm.Double">4.17</d:BC_1MONTH>` or `<d:BC_4MONTH m:null="true" />`
+//! so only those elements are read and everything else in the document is skipped.
+//! Elements are matched by namespace so any prefix the feed declares works
+use crate::error::TreasuryCurveError;
+use quick_xml::{
+    events::{BytesStart, Event},
+    name::{Namespace, ResolveResult},
+    NsReader,
+};
+
+const ATOM: &[u8] = b"http://www.w3.org/2005/Atom";
+const DATA: &[u8] = b"http://schemas.microsoft.com/ado/2007/08/dataservices";
+const METADATA: &[u8] = b"http://schemas.microsoft.com/ado/2007/08/dataservices/metadata";
+
+/// one `<m:properties>` element as (field name, value) pairs, `None` for null values
+pub(crate) type Properties = Vec<(String, Option<String>)>;
+
+/// grab the properties of every entry in the feed, in document order
+pub(crate) fn properties(xml: &str) -> Result<Vec<Properties>, TreasuryCurveError> {
+    let mut reader = NsReader::from_str(xml);
+    reader.config_mut().trim_text(true);
+    let mut entries = vec![];
+    let mut atom_entries = 0;
+    let mut current: Option<Properties> = None;
+    // name, null attribute and text of the field being read
+    let mut field: Option<(String, bool, String)> = None;
+    loop {
+        match reader.read_resolved_event().map_err(xml_error)? {
+            (ns, Event::Start(e)) => {
+                if in_namespace(&ns, ATOM) && e.local_name().as_ref() == b"entry" {
+                    atom_entries += 1;
+                } else if in_namespace(&ns, METADATA) && e.local_name().as_ref() == b"properties" {
+                    current = Some(vec![]);
+                } else if current.is_some() && in_namespace(&ns, DATA) {
+                    field = Some((local_name(&e)?, is_null(&reader, &e)?, String::new()));
+                }
+            }
+            (ns, Event::Empty(e)) => {
+                if in_namespace(&ns, METADATA) && e.local_name().as_ref() == b"properties" {
+                    entries.push(vec![]);
+                } else if let (Some(properties), true) = (&mut current, in_namespace(&ns, DATA)) {
+                    // empty element has no value
+                    properties.push((local_name(&e)?, None));
+                }
+            }
+            (_, Event::Text(text)) => {
+                if let Some((_, _, value)) = &mut field {
+                    value.push_str(&text.unescape().map_err(xml_error)?);
+                }
+            }
+            (_, Event::CData(text)) => {
+                if let Some((_, _, value)) = &mut field {
+                    value.push_str(&String::from_utf8_lossy(&text));
+                }
+            }
+            (ns, Event::End(e)) => {
+                if in_namespace(&ns, METADATA) && e.local_name().as_ref() == b"properties" {
+                    entries.extend(current.take());
+                } else if let (Some((name, null, value)), Some(properties)) =
+                    (field.take(), &mut current)
+                {
+                    let value = (!null && !value.is_empty()).then_some(value);
+                    properties.push((name, value));
+                }
+            }
+            (_, Event::Eof) => break,
+            _ => {}
+        }
+    }
+    if current.is_some() {
+        return Err(xml_error("unclosed <m:properties>"));
+    }
+    // a feed with entries but no properties isn't one we can read, ie. the namespace changed
+    if entries.len() < atom_entries {
+        return Err(xml_error(format!(
+            "{} of {atom_entries} entries have no <m:properties>",
+            atom_entries - entries.len()
+        )));
+    }
+    Ok(entries)
+}
+
+fn in_namespace(ns: &ResolveResult, uri: &[u8]) -> bool {
+    matches!(ns, ResolveResult::Bound(Namespace(n)) if *n == uri)
+}
+
+fn local_name(e: &BytesStart) -> Result<String, TreasuryCurveError> {
+    std::str::from_utf8(e.local_name().as_ref())
+        .map(str::to_string)
+        .map_err(xml_error)
+}
+
+// `m:null="true"` marks a field without a value
+fn is_null(reader: &NsReader<&[u8]>, e: &BytesStart) -> Result<bool, TreasuryCurveError> {
+    for attribute in e.attributes() {
+        let attribute = attribute.map_err(xml_error)?;
+        let (ns, name) = reader.resolve_attribute(attribute.key);
+        if in_namespace(&ns, METADATA) && name.as_ref() == b"null" {
+            return Ok(attribute.unescape_value().map_err(xml_error)? == "true");
+        }
+    }
+    Ok(false)
+}
+
+fn xml_error(e: impl std::fmt::Display) -> TreasuryCurveError {
+    TreasuryCurveError::WebParseXml(e.to_string())
+}
+
+/// feed with a single entry holding `properties`, for tests
+#[cfg(test)]
+pub(crate) fn feed(properties: &str) -> String {
+    format!(
+        r#"<feed xmlns:d="http://schemas.microsoft.com/ado/2007/08/dataservices" xmlns:m="http://schemas.microsoft.com/ado/2007/08/dataservices/metadata" xmlns="http://www.w3.org/2005/Atom">
+        <entry><content type="application/xml">{properties}</content></entry></feed>"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, value: Option<&str>) -> (String, Option<String>) {
+        (name.to_string(), value.map(str::to_string))
+    }
+
+    #[test]
+    fn reads_values_and_nulls() {
+        let xml = feed(
+            r#"<m:properties>
+            <d:Id m:type="Edm.Int32">8309</d:Id>
+            <d:NEW_DATE m:type="Edm.DateTime">2023-01-03T00:00:00</d:NEW_DATE>
+            <d:BC_1MONTH m:type="Edm.Double">4.17</d:BC_1MONTH>
+            <d:BC_2MONTH m:type="Edm.Double" m:null="true" />
+            <d:BC_3MONTH m:type="Edm.Double" m:null="true"></d:BC_3MONTH>
+        </m:properties>"#,
+        );
+        assert_eq!(
+            properties(&xml).unwrap(),
+            vec![vec![
+                field("Id", Some("8309")),
+                field("NEW_DATE", Some("2023-01-03T00:00:00")),
+                field("BC_1MONTH", Some("4.17")),
+                field("BC_2MONTH", None),
+                field("BC_3MONTH", None),
+            ]]
+        );
+        assert!(properties("<feed></feed>").unwrap().is_empty());
+        assert!(properties(&feed("<m:properties><d:BC_1MONTH>4.17</m:properties>")).is_err());
+    }
+
+    #[test]
+    fn matches_namespaces_not_prefixes() {
+        let xml = r#"<a:feed xmlns:a="http://www.w3.org/2005/Atom"><a:entry><a:content>
+            <meta:properties xmlns:meta="http://schemas.microsoft.com/ado/2007/08/dataservices/metadata"
+                xmlns:data="http://schemas.microsoft.com/ado/2007/08/dataservices">
+                <data:NEW_DATE>2023-01-03T00:00:00</data:NEW_DATE>
+                <data:NOTE>a &amp; b</data:NOTE>
+                <data:BC_1MONTH meta:null="true" />
+            </meta:properties></a:content></a:entry></a:feed>"#;
+        assert_eq!(
+            properties(xml).unwrap(),
+            vec![vec![
+                field("NEW_DATE", Some("2023-01-03T00:00:00")),
+                field("NOTE", Some("a & b")),
+                field("BC_1MONTH", None),
+            ]]
+        );
+
+        // entries whose properties aren't in the metadata namespace are an error, not an empty feed
+        let unknown = r#"<feed xmlns="http://www.w3.org/2005/Atom" xmlns:m="urn:other"><entry>
+            <m:properties><m:NEW_DATE>2023-01-03T00:00:00</m:NEW_DATE></m:properties>
+            </entry></feed>"#;
+        assert!(matches!(
+            properties(unknown),
+            Err(TreasuryCurveError::WebParseXml(_))
+        ));
+    }
+}